@@ -0,0 +1,110 @@
+use crate::data::{Dataset, Features};
+use crate::tree::ClassificationTree;
+use crate::{Label, Model};
+
+use ndarray::{Array1, ArrayBase, ArrayView1, ArrayView2, Data, Ix2};
+use std::borrow::Cow;
+
+impl<S> Features for ArrayBase<S, Ix2>
+where
+    S: Data<Elem = f64> + Sync,
+{
+    fn n_rows(&self) -> usize {
+        self.nrows()
+    }
+
+    fn n_columns(&self) -> usize {
+        self.ncols()
+    }
+
+    fn value(&self, row: usize, column: usize) -> f64 {
+        self[[row, column]]
+    }
+
+    fn row(&self, row: usize) -> Cow<'_, [f64]> {
+        let row = ArrayBase::row(self, row);
+        match row.to_slice() {
+            Some(row) => Cow::Borrowed(row),
+            None => Cow::Owned(row.to_vec()),
+        }
+    }
+
+    fn column(&self, column: usize) -> Cow<'_, [f64]> {
+        let column = ArrayBase::column(self, column);
+        match column.to_slice() {
+            Some(column) => Cow::Borrowed(column),
+            None => Cow::Owned(column.to_vec()),
+        }
+    }
+
+    fn to_dataset(&self) -> Cow<'_, Dataset> {
+        // iterating the transpose visits the values column by column
+        Cow::Owned(Dataset::from_column_major(
+            self.t().iter().copied().collect(),
+            self.nrows(),
+            self.ncols(),
+        ))
+    }
+}
+
+impl<L: Label> ClassificationTree<L> {
+    /// Fit the tree on ndarray features and targets
+    ///
+    /// # Arguments
+    /// * `features` - one record per row
+    /// * `targets` - the target of each record
+    pub async fn fit_array(&mut self, features: ArrayView2<'_, f64>, targets: ArrayView1<'_, L>) {
+        let targets = match targets.to_slice() {
+            Some(targets) => Cow::Borrowed(targets),
+            None => Cow::Owned(targets.to_vec()),
+        };
+        self.fit(&features, &targets).await
+    }
+
+    /// Predict the class of each row of ndarray features
+    ///
+    /// # Arguments
+    /// * `features` - one record per row
+    ///
+    /// # Returns
+    /// the predicted class of each record
+    pub async fn predict_array(&self, features: ArrayView2<'_, f64>) -> Array1<L> {
+        Array1::from_vec(self.predict(&features).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tree::ClassificationTreeBuilder;
+    use crate::utils::{load_milk_test_dataset, load_milk_train_dataset};
+    use crate::Model;
+    use ndarray::{Array1, Array2, ShapeBuilder};
+
+    #[tokio::test]
+    async fn test_fit_array() {
+        let to_array = |rows: &[Vec<f64>]| {
+            Array2::from_shape_fn((rows.len(), rows[0].len()), |(row, column)| {
+                rows[row][column]
+            })
+        };
+        let (features, targets) = load_milk_train_dataset();
+        let (test_features, _) = load_milk_test_dataset();
+
+        let mut model = ClassificationTreeBuilder::default().build().unwrap();
+        model.fit(&features, &targets).await;
+        let expected = model.predict(&test_features).await;
+
+        let mut array_model = ClassificationTreeBuilder::default().build().unwrap();
+        let column_major =
+            Array2::from_shape_fn((features.len(), features[0].len()).f(), |(row, column)| {
+                features[row][column]
+            });
+        array_model
+            .fit_array(column_major.view(), Array1::from_vec(targets).view())
+            .await;
+        let predictions = array_model
+            .predict_array(to_array(&test_features).view())
+            .await;
+        assert_eq!(predictions.to_vec(), expected);
+    }
+}
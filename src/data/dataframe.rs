@@ -0,0 +1,239 @@
+use crate::data::Dataset;
+use crate::tree::ClassificationTree;
+use crate::{Label, Model};
+
+use polars::prelude::{
+    Column, DataFrame, DataType, NamedFrom, PlSmallStr, PolarsError, PolarsResult, Series,
+};
+
+/// Labels that can be read from and written to a Polars column
+pub trait SeriesLabel: Label + Sized {
+    /// Read a label from each value of a column
+    ///
+    /// # Errors
+    /// if the column can't be cast to the label type or has nulls
+    fn from_column(column: &Column) -> PolarsResult<Vec<Self>>;
+
+    /// Collect labels into a series
+    fn to_series(name: PlSmallStr, labels: Vec<Self>) -> Series;
+}
+
+/// Integer labels are read through an `Int64` column
+macro_rules! impl_series_label_for_int {
+    ($($label:ty),*) => {
+        $(
+            impl SeriesLabel for $label {
+                fn from_column(column: &Column) -> PolarsResult<Vec<Self>> {
+                    let column = column.strict_cast(&DataType::Int64)?;
+                    column
+                        .i64()?
+                        .into_iter()
+                        .map(|label| {
+                            let label = label.ok_or_else(|| null_target(&column))?;
+                            Self::try_from(label).map_err(|_| {
+                                PolarsError::ComputeError(
+                                    format!("target {label} is out of range").into(),
+                                )
+                            })
+                        })
+                        .collect()
+                }
+
+                fn to_series(name: PlSmallStr, labels: Vec<Self>) -> Series {
+                    Series::new(
+                        name,
+                        labels.into_iter().map(|label| label as i64).collect::<Vec<_>>(),
+                    )
+                }
+            }
+        )*
+    };
+}
+
+impl_series_label_for_int!(isize, i64, i32);
+
+impl SeriesLabel for String {
+    fn from_column(column: &Column) -> PolarsResult<Vec<Self>> {
+        let column = column.strict_cast(&DataType::String)?;
+        column
+            .str()?
+            .into_iter()
+            .map(|label| label.map(String::from).ok_or_else(|| null_target(&column)))
+            .collect()
+    }
+
+    fn to_series(name: PlSmallStr, labels: Vec<Self>) -> Series {
+        Series::new(name, labels)
+    }
+}
+
+fn null_target(column: &Column) -> PolarsError {
+    PolarsError::ComputeError(format!("target column {} has nulls", column.name()).into())
+}
+
+/// Copy the columns of a data frame into a dataset, named by the column names
+///
+/// Every column is cast to `f64` and nulls are read as missing values.
+///
+/// # Arguments
+/// * `data_frame` - the data frame to copy
+/// * `exclude` - a column to leave out, such as the target
+///
+/// # Errors
+/// if a column can't be cast to `f64`
+pub fn dataframe_to_dataset(
+    data_frame: &DataFrame,
+    exclude: Option<&str>,
+) -> PolarsResult<Dataset> {
+    let columns = data_frame
+        .get_columns()
+        .iter()
+        .filter(|column| Some(column.name().as_str()) != exclude)
+        .collect::<Vec<_>>();
+    let mut values = Vec::with_capacity(data_frame.height() * columns.len());
+    for column in &columns {
+        let column = column.strict_cast(&DataType::Float64)?;
+        values.extend(
+            column
+                .f64()?
+                .into_iter()
+                .map(|value| value.unwrap_or(f64::NAN)),
+        );
+    }
+    let feature_names = columns
+        .iter()
+        .map(|column| column.name().to_string())
+        .collect();
+    Ok(
+        Dataset::from_column_major(values, data_frame.height(), columns.len())
+            .with_feature_names(feature_names),
+    )
+}
+
+impl<L: SeriesLabel> ClassificationTree<L> {
+    /// Fit the tree on a data frame
+    ///
+    /// # Arguments
+    /// * `data_frame` - the features along with the targets
+    /// * `target` - the name of the column holding the targets, every other column is a feature
+    ///
+    /// # Errors
+    /// if the target column doesn't exist or any column can't be read
+    pub async fn fit_dataframe(
+        &mut self,
+        data_frame: &DataFrame,
+        target: &str,
+    ) -> PolarsResult<()> {
+        let targets = L::from_column(data_frame.column(target)?)?;
+        let features = dataframe_to_dataset(data_frame, Some(target))?;
+        self.fit(&features, &targets).await;
+        Ok(())
+    }
+
+    /// Predict the class of each row of a data frame
+    ///
+    /// Features are selected by the names of the features the tree was fit on so the data frame's
+    /// columns can be in any order and other columns are ignored. Trees fit on unnamed features
+    /// read every column in order.
+    ///
+    /// # Arguments
+    /// * `data_frame` - the features, with the same columns the tree was fit on
+    ///
+    /// # Returns
+    /// a series named `prediction` holding the predicted class of each row
+    ///
+    /// # Errors
+    /// if a feature the tree was fit on is missing or any column can't be read
+    pub async fn predict_dataframe(&self, data_frame: &DataFrame) -> PolarsResult<Series> {
+        let features = match self.feature_names() {
+            Some(feature_names) => {
+                dataframe_to_dataset(&data_frame.select(feature_names.iter().cloned())?, None)?
+            }
+            None => dataframe_to_dataset(data_frame, None)?,
+        };
+        let predictions = self.predict(&features).await;
+        Ok(L::to_series("prediction".into(), predictions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::ClassificationTreeBuilder;
+    use crate::utils::{load_milk_test_dataset, load_milk_train_dataset};
+
+    #[tokio::test]
+    async fn test_fit_dataframe() {
+        let to_data_frame = |rows: &[Vec<f64>]| {
+            let columns = (0..rows[0].len())
+                .map(|column| {
+                    let values = rows.iter().map(|row| row[column]).collect::<Vec<_>>();
+                    Column::new(format!("feature_{column}").into(), values)
+                })
+                .collect();
+            DataFrame::new(columns).unwrap()
+        };
+        let (features, targets) = load_milk_train_dataset();
+        let (test_features, _) = load_milk_test_dataset();
+
+        let mut model = ClassificationTreeBuilder::default().build().unwrap();
+        model.fit(&features, &targets).await;
+        let expected = model.predict(&test_features).await;
+
+        let mut data_frame = to_data_frame(&features);
+        let grades = targets
+            .iter()
+            .map(|target| ["low", "medium", "high"][*target as usize])
+            .collect::<Vec<_>>();
+        data_frame
+            .with_column(Column::new("grade".into(), grades))
+            .unwrap();
+        let mut frame_model = ClassificationTreeBuilder::<String>::default()
+            .build()
+            .unwrap();
+        frame_model
+            .fit_dataframe(&data_frame, "grade")
+            .await
+            .unwrap();
+        let predictions = frame_model
+            .predict_dataframe(&to_data_frame(&test_features))
+            .await
+            .unwrap();
+        let predictions = predictions
+            .str()
+            .unwrap()
+            .into_iter()
+            .map(|prediction| prediction.unwrap())
+            .collect::<Vec<_>>();
+        let expected = expected
+            .iter()
+            .map(|target| ["low", "medium", "high"][*target as usize])
+            .collect::<Vec<_>>();
+        assert_eq!(predictions, expected);
+
+        // columns are matched by name, not position
+        let shuffled = to_data_frame(&test_features);
+        let mut names = shuffled.get_column_names_str();
+        names.reverse();
+        let mut shuffled = shuffled.select(names).unwrap();
+        shuffled
+            .with_column(Column::new("id".into(), vec![0; test_features.len()]))
+            .unwrap();
+        let shuffled_predictions = frame_model.predict_dataframe(&shuffled).await.unwrap();
+        let shuffled_predictions = shuffled_predictions
+            .str()
+            .unwrap()
+            .into_iter()
+            .map(|prediction| prediction.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(shuffled_predictions, expected);
+        let missing_feature = shuffled.drop("feature_0").unwrap();
+        assert!(frame_model
+            .predict_dataframe(&missing_feature)
+            .await
+            .is_err());
+
+        let missing = frame_model.fit_dataframe(&data_frame, "missing").await;
+        assert!(missing.is_err());
+    }
+}
@@ -0,0 +1,173 @@
+/// A dense matrix of features stored contiguously column by column
+///
+/// # Arguments
+/// * `values` - every value, the first column followed by the second and so on
+/// * `n_rows` - the number of records
+/// * `n_columns` - the number of features
+/// * `feature_names` - optional names of the features
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Dataset {
+    values: Vec<f64>,
+    n_rows: usize,
+    n_columns: usize,
+    feature_names: Option<Vec<String>>,
+}
+
+impl Dataset {
+    /// Build a dataset from its columns
+    ///
+    /// # Panics
+    /// if the columns are of differing lengths
+    pub fn from_columns(columns: Vec<Vec<f64>>) -> Self {
+        let n_columns = columns.len();
+        let n_rows = columns
+            .first()
+            .map(|column| column.len())
+            .unwrap_or_default();
+        assert!(
+            columns.iter().all(|column| column.len() == n_rows),
+            "Columns are of differing length, cannot build dataset"
+        );
+        Self {
+            values: columns.into_iter().flatten().collect(),
+            n_rows,
+            n_columns,
+            feature_names: None,
+        }
+    }
+
+    /// Build a dataset from values already stored column by column
+    ///
+    /// # Panics
+    /// if there aren't `n_rows * n_columns` values
+    pub fn from_column_major(values: Vec<f64>, n_rows: usize, n_columns: usize) -> Self {
+        assert_eq!(
+            values.len(),
+            n_rows * n_columns,
+            "Number of values doesn't match the shape, cannot build dataset"
+        );
+        Self {
+            values,
+            n_rows,
+            n_columns,
+            feature_names: None,
+        }
+    }
+
+    /// Build a dataset from records
+    ///
+    /// # Panics
+    /// if the records are of differing lengths
+    pub fn from_rows(rows: &[Vec<f64>]) -> Self {
+        let n_rows = rows.len();
+        let n_columns = rows.first().map(|row| row.len()).unwrap_or_default();
+        assert!(
+            rows.iter().all(|row| row.len() == n_columns),
+            "Records are of differing length, cannot build dataset"
+        );
+        let mut values = Vec::with_capacity(n_rows * n_columns);
+        for column in 0..n_columns {
+            values.extend(rows.iter().map(|row| row[column]));
+        }
+        Self {
+            values,
+            n_rows,
+            n_columns,
+            feature_names: None,
+        }
+    }
+
+    /// Name the features of the dataset
+    ///
+    /// # Panics
+    /// if there isn't exactly one name per column
+    pub fn with_feature_names(mut self, feature_names: Vec<String>) -> Self {
+        assert!(
+            feature_names.len() == self.n_columns,
+            "Expected {} feature names, got {}",
+            self.n_columns,
+            feature_names.len()
+        );
+        self.feature_names = Some(feature_names);
+        self
+    }
+
+    /// The number of records and features, `(n_rows, n_columns)`
+    pub fn shape(&self) -> (usize, usize) {
+        (self.n_rows, self.n_columns)
+    }
+
+    pub fn n_rows(&self) -> usize {
+        self.n_rows
+    }
+
+    pub fn n_columns(&self) -> usize {
+        self.n_columns
+    }
+
+    pub fn feature_names(&self) -> Option<&[String]> {
+        self.feature_names.as_deref()
+    }
+
+    /// A view of one column, without copying
+    pub fn column(&self, column: usize) -> &[f64] {
+        &self.values[column * self.n_rows..(column + 1) * self.n_rows]
+    }
+
+    /// A mutable view of one column
+    pub fn column_mut(&mut self, column: usize) -> &mut [f64] {
+        &mut self.values[column * self.n_rows..(column + 1) * self.n_rows]
+    }
+
+    /// Iterate over views of every column
+    pub fn columns(&self) -> impl Iterator<Item = &[f64]> {
+        (0..self.n_columns).map(|column| self.column(column))
+    }
+
+    pub fn value(&self, row: usize, column: usize) -> f64 {
+        self.values[column * self.n_rows + row]
+    }
+
+    /// Copy a subset of the records into a new dataset, in the given order
+    pub fn select_rows(&self, rows: &[usize]) -> Dataset {
+        let mut values = Vec::with_capacity(rows.len() * self.n_columns);
+        for column in self.columns() {
+            values.extend(rows.iter().map(|row| column[*row]));
+        }
+        Dataset {
+            values,
+            n_rows: rows.len(),
+            n_columns: self.n_columns,
+            feature_names: self.feature_names.clone(),
+        }
+    }
+
+    /// Copy one record out of the dataset
+    pub fn row(&self, row: usize) -> Vec<f64> {
+        (0..self.n_columns)
+            .map(|column| self.value(row, column))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dataset() {
+        let rows = vec![vec![1.0, 2.0], vec![3.0, 4.0], vec![5.0, 6.0]];
+        let dataset = Dataset::from_rows(&rows);
+        assert_eq!(dataset.shape(), (3, 2));
+        assert_eq!(dataset.column(1), [2.0, 4.0, 6.0]);
+        assert_eq!(dataset.row(2), rows[2]);
+        assert_eq!(
+            dataset,
+            Dataset::from_columns(vec![vec![1.0, 3.0, 5.0], vec![2.0, 4.0, 6.0]])
+        );
+        assert_eq!(dataset.select_rows(&[2, 0]).row(0), rows[2]);
+
+        let dataset = dataset.with_feature_names(vec!["ph".to_string(), "fat".to_string()]);
+        assert_eq!(dataset.feature_names().unwrap()[1], "fat");
+    }
+}
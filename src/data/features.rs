@@ -0,0 +1,133 @@
+use crate::data::{CscMatrix, Dataset};
+use std::borrow::Cow;
+
+/// A table of features models can be fit on and predict from
+///
+/// Implemented for [`Dataset`], for sparse [`CsrMatrix`](crate::data::CsrMatrix) and
+/// [`CscMatrix`] and for records stored row by row as `Vec<Vec<f64>>`.
+pub trait Features: Sync {
+    fn n_rows(&self) -> usize;
+
+    fn n_columns(&self) -> usize;
+
+    fn value(&self, row: usize, column: usize) -> f64;
+
+    /// One record, borrowed if the features are stored row by row
+    fn row(&self, row: usize) -> Cow<'_, [f64]> {
+        Cow::Owned(
+            (0..self.n_columns())
+                .map(|column| self.value(row, column))
+                .collect(),
+        )
+    }
+
+    /// One column, borrowed if the features are stored column by column
+    fn column(&self, column: usize) -> Cow<'_, [f64]> {
+        Cow::Owned(
+            (0..self.n_rows())
+                .map(|row| self.value(row, column))
+                .collect(),
+        )
+    }
+
+    fn feature_names(&self) -> Option<&[String]> {
+        None
+    }
+
+    /// The features as a [`Dataset`], only copying if they aren't one already
+    fn to_dataset(&self) -> Cow<'_, Dataset> {
+        let dataset = Dataset::from_columns(
+            (0..self.n_columns())
+                .map(|column| self.column(column).into_owned())
+                .collect(),
+        );
+        Cow::Owned(match self.feature_names() {
+            Some(feature_names) => dataset.with_feature_names(feature_names.to_vec()),
+            None => dataset,
+        })
+    }
+
+    /// The features as a [`CscMatrix`] if they're stored sparsely, trees are fit on sparse
+    /// features without densifying them
+    fn to_csc(&self) -> Option<Cow<'_, CscMatrix>> {
+        None
+    }
+}
+
+impl Features for Dataset {
+    fn n_rows(&self) -> usize {
+        Dataset::n_rows(self)
+    }
+
+    fn n_columns(&self) -> usize {
+        Dataset::n_columns(self)
+    }
+
+    fn value(&self, row: usize, column: usize) -> f64 {
+        Dataset::value(self, row, column)
+    }
+
+    fn column(&self, column: usize) -> Cow<'_, [f64]> {
+        Cow::Borrowed(Dataset::column(self, column))
+    }
+
+    fn feature_names(&self) -> Option<&[String]> {
+        Dataset::feature_names(self)
+    }
+
+    fn to_dataset(&self) -> Cow<'_, Dataset> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl Features for [Vec<f64>] {
+    fn n_rows(&self) -> usize {
+        self.len()
+    }
+
+    fn n_columns(&self) -> usize {
+        self.first().map(|row| row.len()).unwrap_or_default()
+    }
+
+    fn value(&self, row: usize, column: usize) -> f64 {
+        self[row][column]
+    }
+
+    fn row(&self, row: usize) -> Cow<'_, [f64]> {
+        Cow::Borrowed(&self[row])
+    }
+
+    fn to_dataset(&self) -> Cow<'_, Dataset> {
+        Cow::Owned(Dataset::from_rows(self))
+    }
+}
+
+/// Delegate to the slice implementation for containers of records
+macro_rules! impl_features_for_rows {
+    ($($impl_generics:tt)*) => {
+        impl $($impl_generics)* {
+            fn n_rows(&self) -> usize {
+                self.as_slice().n_rows()
+            }
+
+            fn n_columns(&self) -> usize {
+                self.as_slice().n_columns()
+            }
+
+            fn value(&self, row: usize, column: usize) -> f64 {
+                self.as_slice().value(row, column)
+            }
+
+            fn row(&self, row: usize) -> Cow<'_, [f64]> {
+                self.as_slice().row(row)
+            }
+
+            fn to_dataset(&self) -> Cow<'_, Dataset> {
+                self.as_slice().to_dataset()
+            }
+        }
+    };
+}
+
+impl_features_for_rows!(Features for Vec<Vec<f64>>);
+impl_features_for_rows!(<const N: usize> Features for [Vec<f64>; N]);
@@ -0,0 +1,12 @@
+#[cfg(feature = "ndarray")]
+mod array;
+#[cfg(feature = "polars")]
+mod dataframe;
+mod dataset;
+mod features;
+mod sparse;
+#[cfg(feature = "polars")]
+pub use dataframe::*;
+pub use dataset::*;
+pub use features::*;
+pub use sparse::*;
@@ -0,0 +1,361 @@
+use crate::data::{Dataset, Features};
+use std::borrow::Cow;
+
+/// Compressed storage of the non-zero values of a matrix, one lane per row or column
+///
+/// # Arguments
+/// * `indptr` - lane `i` is stored at `indptr[i]..indptr[i + 1]`
+/// * `indices` - the position of each stored value within its lane, ascending within each lane
+/// * `values` - the stored values
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Compressed {
+    indptr: Vec<usize>,
+    indices: Vec<usize>,
+    values: Vec<f64>,
+}
+
+impl Compressed {
+    /// # Panics
+    /// if the arrays don't describe `n_lanes` lanes with positions below `lane_length`
+    fn new(
+        n_lanes: usize,
+        lane_length: usize,
+        indptr: Vec<usize>,
+        indices: Vec<usize>,
+        values: Vec<f64>,
+    ) -> Self {
+        assert_eq!(
+            indptr.len(),
+            n_lanes + 1,
+            "Expected {} index pointers, got {}",
+            n_lanes + 1,
+            indptr.len()
+        );
+        assert!(
+            indptr[0] == 0 && indptr.windows(2).all(|pair| pair[0] <= pair[1]),
+            "Index pointers must start at 0 and be non-decreasing"
+        );
+        assert!(
+            indices.len() == values.len() && indices.len() == indptr[n_lanes],
+            "Indices and values must have one entry per stored value"
+        );
+        let storage = Self {
+            indptr,
+            indices,
+            values,
+        };
+        assert!(
+            (0..n_lanes).all(|lane| {
+                let (indices, _) = storage.lane(lane);
+                indices.windows(2).all(|pair| pair[0] < pair[1])
+                    && indices.last().is_none_or(|index| *index < lane_length)
+            }),
+            "Indices must be in bounds and strictly increasing within each lane"
+        );
+        storage
+    }
+
+    /// Store the non-zero values of dense lanes
+    fn from_lanes(lanes: impl Iterator<Item = impl IntoIterator<Item = f64>>) -> Self {
+        let mut storage = Self {
+            indptr: vec![0],
+            ..Default::default()
+        };
+        for lane in lanes {
+            for (index, value) in lane.into_iter().enumerate() {
+                if value != 0.0 {
+                    storage.indices.push(index);
+                    storage.values.push(value);
+                }
+            }
+            storage.indptr.push(storage.indices.len());
+        }
+        storage
+    }
+
+    fn lane(&self, lane: usize) -> (&[usize], &[f64]) {
+        let range = self.indptr[lane]..self.indptr[lane + 1];
+        (&self.indices[range.clone()], &self.values[range])
+    }
+
+    fn get(&self, lane: usize, index: usize) -> f64 {
+        let (indices, values) = self.lane(lane);
+        match indices.binary_search(&index) {
+            Ok(position) => values[position],
+            Err(_) => 0.0,
+        }
+    }
+
+    fn dense_lane(&self, lane: usize, lane_length: usize) -> Vec<f64> {
+        let mut dense = vec![0.0; lane_length];
+        let (indices, values) = self.lane(lane);
+        for (index, value) in indices.iter().zip(values) {
+            dense[*index] = *value;
+        }
+        dense
+    }
+
+    /// Swap rows for columns, O(number of stored values)
+    fn transpose(&self, lane_length: usize) -> Self {
+        let mut indptr = vec![0; lane_length + 1];
+        for index in &self.indices {
+            indptr[index + 1] += 1;
+        }
+        for i in 0..lane_length {
+            indptr[i + 1] += indptr[i];
+        }
+        let mut next = indptr.clone();
+        let mut indices = vec![0; self.indices.len()];
+        let mut values = vec![0.0; self.values.len()];
+        for lane in 0..self.indptr.len().saturating_sub(1) {
+            let (lane_indices, lane_values) = self.lane(lane);
+            for (index, value) in lane_indices.iter().zip(lane_values) {
+                indices[next[*index]] = lane;
+                values[next[*index]] = *value;
+                next[*index] += 1;
+            }
+        }
+        Self {
+            indptr,
+            indices,
+            values,
+        }
+    }
+}
+
+/// A sparse matrix of features stored row by row (compressed sparse row), for prediction
+///
+/// Values that aren't stored are zero, missing values can be stored as `NaN`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CsrMatrix {
+    n_rows: usize,
+    n_columns: usize,
+    storage: Compressed,
+}
+
+impl CsrMatrix {
+    /// Build a matrix from its compressed arrays
+    ///
+    /// # Arguments
+    /// * `n_rows` - the number of records
+    /// * `n_columns` - the number of features
+    /// * `indptr` - row `i` is stored at `indptr[i]..indptr[i + 1]`, `n_rows + 1` long
+    /// * `indices` - the column of each stored value, ascending within each row
+    /// * `values` - the stored values
+    ///
+    /// # Panics
+    /// if the arrays don't describe a valid matrix of the given shape
+    pub fn new(
+        n_rows: usize,
+        n_columns: usize,
+        indptr: Vec<usize>,
+        indices: Vec<usize>,
+        values: Vec<f64>,
+    ) -> Self {
+        Self {
+            n_rows,
+            n_columns,
+            storage: Compressed::new(n_rows, n_columns, indptr, indices, values),
+        }
+    }
+
+    /// Store the non-zero values of dense features
+    pub fn from_dense(features: &dyn Features) -> Self {
+        Self {
+            n_rows: features.n_rows(),
+            n_columns: features.n_columns(),
+            storage: Compressed::from_lanes(
+                (0..features.n_rows()).map(|row| features.row(row).into_owned()),
+            ),
+        }
+    }
+
+    /// The number of records and features, `(n_rows, n_columns)`
+    pub fn shape(&self) -> (usize, usize) {
+        (self.n_rows, self.n_columns)
+    }
+
+    /// The number of stored values
+    pub fn nnz(&self) -> usize {
+        self.storage.values.len()
+    }
+
+    /// The columns and values stored for one record
+    pub fn row_entries(&self, row: usize) -> (&[usize], &[f64]) {
+        self.storage.lane(row)
+    }
+
+    /// The same matrix stored column by column
+    pub fn to_csc(&self) -> CscMatrix {
+        CscMatrix {
+            n_rows: self.n_rows,
+            n_columns: self.n_columns,
+            storage: self.storage.transpose(self.n_columns),
+        }
+    }
+}
+
+/// A sparse matrix of features stored column by column (compressed sparse column), for training
+///
+/// Trees are fit on these without densifying them, only the stored values of a column are
+/// visited when searching for splits. Values that aren't stored are zero, missing values can be
+/// stored as `NaN`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CscMatrix {
+    n_rows: usize,
+    n_columns: usize,
+    storage: Compressed,
+}
+
+impl CscMatrix {
+    /// Build a matrix from its compressed arrays
+    ///
+    /// # Arguments
+    /// * `n_rows` - the number of records
+    /// * `n_columns` - the number of features
+    /// * `indptr` - column `i` is stored at `indptr[i]..indptr[i + 1]`, `n_columns + 1` long
+    /// * `indices` - the row of each stored value, ascending within each column
+    /// * `values` - the stored values
+    ///
+    /// # Panics
+    /// if the arrays don't describe a valid matrix of the given shape
+    pub fn new(
+        n_rows: usize,
+        n_columns: usize,
+        indptr: Vec<usize>,
+        indices: Vec<usize>,
+        values: Vec<f64>,
+    ) -> Self {
+        Self {
+            n_rows,
+            n_columns,
+            storage: Compressed::new(n_columns, n_rows, indptr, indices, values),
+        }
+    }
+
+    /// Store the non-zero values of dense features
+    pub fn from_dense(features: &dyn Features) -> Self {
+        Self {
+            n_rows: features.n_rows(),
+            n_columns: features.n_columns(),
+            storage: Compressed::from_lanes(
+                (0..features.n_columns()).map(|column| features.column(column).into_owned()),
+            ),
+        }
+    }
+
+    /// The number of records and features, `(n_rows, n_columns)`
+    pub fn shape(&self) -> (usize, usize) {
+        (self.n_rows, self.n_columns)
+    }
+
+    /// The number of stored values
+    pub fn nnz(&self) -> usize {
+        self.storage.values.len()
+    }
+
+    /// The rows and values stored for one feature
+    pub fn column_entries(&self, column: usize) -> (&[usize], &[f64]) {
+        self.storage.lane(column)
+    }
+
+    /// The same matrix stored row by row
+    pub fn to_csr(&self) -> CsrMatrix {
+        CsrMatrix {
+            n_rows: self.n_rows,
+            n_columns: self.n_columns,
+            storage: self.storage.transpose(self.n_rows),
+        }
+    }
+}
+
+impl Features for CsrMatrix {
+    fn n_rows(&self) -> usize {
+        self.n_rows
+    }
+
+    fn n_columns(&self) -> usize {
+        self.n_columns
+    }
+
+    fn value(&self, row: usize, column: usize) -> f64 {
+        self.storage.get(row, column)
+    }
+
+    fn row(&self, row: usize) -> Cow<'_, [f64]> {
+        Cow::Owned(self.storage.dense_lane(row, self.n_columns))
+    }
+
+    fn to_dataset(&self) -> Cow<'_, Dataset> {
+        Cow::Owned(CsrMatrix::to_csc(self).to_dataset().into_owned())
+    }
+
+    fn to_csc(&self) -> Option<Cow<'_, CscMatrix>> {
+        Some(Cow::Owned(CsrMatrix::to_csc(self)))
+    }
+}
+
+impl Features for CscMatrix {
+    fn n_rows(&self) -> usize {
+        self.n_rows
+    }
+
+    fn n_columns(&self) -> usize {
+        self.n_columns
+    }
+
+    fn value(&self, row: usize, column: usize) -> f64 {
+        self.storage.get(column, row)
+    }
+
+    fn column(&self, column: usize) -> Cow<'_, [f64]> {
+        Cow::Owned(self.storage.dense_lane(column, self.n_rows))
+    }
+
+    fn to_dataset(&self) -> Cow<'_, Dataset> {
+        let mut values = vec![0.0; self.n_rows * self.n_columns];
+        for column in 0..self.n_columns {
+            let (rows, column_values) = self.column_entries(column);
+            for (row, value) in rows.iter().zip(column_values) {
+                values[column * self.n_rows + row] = *value;
+            }
+        }
+        Cow::Owned(Dataset::from_column_major(
+            values,
+            self.n_rows,
+            self.n_columns,
+        ))
+    }
+
+    fn to_csc(&self) -> Option<Cow<'_, CscMatrix>> {
+        Some(Cow::Borrowed(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparse_round_trip() {
+        let rows = vec![
+            vec![0.0, 1.5, 0.0],
+            vec![0.0, 0.0, 0.0],
+            vec![-2.0, f64::NAN, 3.0],
+        ];
+        let csr = CsrMatrix::from_dense(&rows);
+        assert_eq!(csr.shape(), (3, 3));
+        assert_eq!(csr.nnz(), 4);
+        assert_eq!(csr.row_entries(2).0, [0, 1, 2]);
+        assert!(csr.value(2, 1).is_nan());
+        assert_eq!(csr.value(1, 1), 0.0);
+
+        let csc = csr.to_csc();
+        assert_eq!(csc.column_entries(0), (&[2][..], &[-2.0][..]));
+        assert!(csc.value(2, 1).is_nan());
+        // missing values aren't equal to themselves, so compare a matrix without any
+        let csr = CsrMatrix::from_dense(&rows[..2].to_vec());
+        assert_eq!(csr.to_csc(), CscMatrix::from_dense(&rows[..2].to_vec()));
+        assert_eq!(csr.to_csc().to_csr(), csr);
+    }
+}
@@ -0,0 +1,4 @@
+mod permutation_importance;
+mod tree_shap;
+pub use permutation_importance::*;
+pub use tree_shap::*;
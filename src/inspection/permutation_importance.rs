@@ -0,0 +1,99 @@
+use crate::data::Features;
+use crate::metrics::Scorer;
+use crate::{Label, Model};
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+/// Permutation importances of each feature
+///
+/// # Arguments
+/// * `baseline_score` - score of the model on the unshuffled features
+/// * `importances` - the drop in score for each repeat, indexed by column then repeat
+/// * `importances_mean` - the mean drop in score of each column
+/// * `importances_std` - the standard deviation of the drop in score of each column
+#[derive(Clone, Debug)]
+pub struct PermutationImportance {
+    pub baseline_score: f64,
+    pub importances: Vec<Vec<f64>>,
+    pub importances_mean: Vec<f64>,
+    pub importances_std: Vec<f64>,
+}
+
+/// Model agnostic feature importance
+///
+/// Each column is shuffled in turn, breaking its relationship with the targets, and the drop in
+/// the model's score is recorded. Unlike impurity-based importances this isn't biased towards
+/// columns with many distinct values.
+///
+/// # Arguments
+/// * `model` - a fitted model
+/// * `features` - features to score the model on, ideally held out from training
+/// * `targets` - targets to score the model on
+/// * `scorer` - the metric or scorer to score the model with
+/// * `n_repeats` - the number of times each column is shuffled
+/// * `seed` - seed for the shuffles
+///
+/// # Returns
+/// the importance of each feature
+pub async fn permutation_importance<M, L, S>(
+    model: &M,
+    features: &dyn Features,
+    targets: &[L],
+    scorer: S,
+    n_repeats: usize,
+    seed: u64,
+) -> PermutationImportance
+where
+    M: Model<L> + Sync,
+    L: Label,
+    S: Scorer<L>,
+{
+    let baseline_score = model.score(features, targets, &scorer).await;
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut importances = vec![];
+    let mut shuffled_features = features.to_dataset().into_owned();
+    for column in 0..shuffled_features.n_columns() {
+        let original_values = shuffled_features.column(column).to_vec();
+        let mut drops = vec![];
+        for _ in 0..n_repeats {
+            shuffled_features.column_mut(column).shuffle(&mut rng);
+            let score = model.score(&shuffled_features, targets, &scorer).await;
+            // a drop is always a change for the worse, whichever way the scorer points
+            drops.push(match scorer.greater_is_better() {
+                true => baseline_score - score,
+                false => score - baseline_score,
+            });
+        }
+        // put the column back before moving on to the next one
+        shuffled_features
+            .column_mut(column)
+            .copy_from_slice(&original_values);
+        importances.push(drops);
+    }
+
+    let importances_mean = importances.iter().map(|drops| mean(drops)).collect::<Vec<_>>();
+    let importances_std = importances
+        .iter()
+        .zip(&importances_mean)
+        .map(|(drops, drops_mean)| {
+            mean(&drops.iter().map(|drop| (drop - drops_mean).powi(2)).collect::<Vec<_>>()).sqrt()
+        })
+        .collect();
+
+    PermutationImportance {
+        baseline_score,
+        importances,
+        importances_mean,
+        importances_std,
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
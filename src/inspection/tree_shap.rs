@@ -0,0 +1,425 @@
+use crate::data::Features;
+use crate::tree::{Arena, ChildType, Node, NodeData, NodeId, NodeType};
+
+/// SHAP values explaining a set of predictions
+///
+/// For every record the expected value plus the sum of its SHAP values is equal to the model's
+/// output for that record.
+///
+/// # Arguments
+/// * `expected_value` - the model's output averaged over the training data
+/// * `values` - the contribution of each feature to each prediction, indexed by record then column
+#[derive(Clone, Debug)]
+pub struct ShapValues {
+    pub expected_value: f64,
+    pub values: Vec<Vec<f64>>,
+}
+
+/// Exact TreeSHAP for a weighted sum of trees
+///
+/// Single trees are explained by passing a single tree with weight one, forests by passing each
+/// of their trees weighted by one over the number of trees and boosted ensembles by passing each
+/// tree weighted by its learning rate. Feature absence is modelled using the total weight of the
+/// training records that reached each node.
+///
+/// # Arguments
+/// * `trees` - the trees of the model and the weight of each tree's output
+/// * `features` - the records to explain
+/// * `leaf_value` - the output of a leaf
+///
+/// # Returns
+/// the SHAP values of each record
+pub fn tree_shap<'a, F>(
+    trees: impl IntoIterator<Item = (&'a Arena, f64)>,
+    features: &dyn Features,
+    leaf_value: F,
+) -> ShapValues
+where
+    F: Fn(&NodeData) -> f64,
+{
+    let mut expected_value = 0.0;
+    let mut values = vec![vec![0f64; features.n_columns()]; features.n_rows()];
+
+    for (arena, weight) in trees {
+        let Some(root) = arena.root() else {
+            continue;
+        };
+        let explainer = TreeExplainer {
+            arena,
+            leaf_value: &leaf_value,
+        };
+        expected_value += weight * explainer.expected_value(root);
+        for (row, phi) in values.iter_mut().enumerate() {
+            let record = features.row(row);
+            let mut tree_phi = vec![0f64; record.len()];
+            explainer.recurse(root, &record, &mut tree_phi, &[], 1.0, 1.0, None);
+            for (total, value) in phi.iter_mut().zip(tree_phi) {
+                *total += weight * value;
+            }
+        }
+    }
+
+    ShapValues {
+        expected_value,
+        values,
+    }
+}
+
+/// An element of the path of unique features from the root to the current node
+#[derive(Clone, Copy, Debug)]
+struct PathElement {
+    feature: Option<usize>,
+    zero_fraction: f64,
+    one_fraction: f64,
+    weight: f64,
+}
+
+struct TreeExplainer<'a, F> {
+    arena: &'a Arena,
+    leaf_value: &'a F,
+}
+
+impl<F> TreeExplainer<'_, F>
+where
+    F: Fn(&NodeData) -> f64,
+{
+    fn node(&self, node_id: NodeId) -> &Node {
+        &self.arena.nodes[node_id.index]
+    }
+
+    /// The hot and cold children of a branch, `None` if the node makes a prediction
+    fn children(&self, node: &Node, record: &[f64]) -> Option<(NodeId, NodeId)> {
+        if node.data.node_type == NodeType::Leaf {
+            return None;
+        }
+        let (first_child, second_child) = (node.first_child?, node.second_child?);
+        match node.data.direction(record) {
+            ChildType::First => Some((first_child, second_child)),
+            ChildType::Second => Some((second_child, first_child)),
+        }
+    }
+
+    fn cover(&self, node_id: NodeId) -> f64 {
+        self.node(node_id).data.weighted_n_samples
+    }
+
+    /// The fraction of a node's training records that reached one of its children, split evenly
+    /// if no weight reached the node
+    fn child_fraction(&self, child: NodeId, node_id: NodeId) -> f64 {
+        match self.cover(node_id) {
+            0.0 => 0.5,
+            cover => self.cover(child) / cover,
+        }
+    }
+
+    /// Output of the tree averaged over the training records that reached a node
+    fn expected_value(&self, node_id: NodeId) -> f64 {
+        let node = self.node(node_id);
+        match (node.data.node_type, node.first_child, node.second_child) {
+            (NodeType::Branch, Some(first_child), Some(second_child)) => {
+                self.child_fraction(first_child, node_id) * self.expected_value(first_child)
+                    + self.child_fraction(second_child, node_id)
+                        * self.expected_value(second_child)
+            }
+            _ => (self.leaf_value)(&node.data),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn recurse(
+        &self,
+        node_id: NodeId,
+        record: &[f64],
+        phi: &mut [f64],
+        parent_path: &[PathElement],
+        zero_fraction: f64,
+        one_fraction: f64,
+        feature: Option<usize>,
+    ) {
+        let mut path = parent_path.to_vec();
+        extend_path(&mut path, zero_fraction, one_fraction, feature);
+
+        let node = self.node(node_id);
+        let Some((hot_child, cold_child)) = self.children(node, record) else {
+            let value = (self.leaf_value)(&node.data);
+            for i in 1..path.len() {
+                let weight = unwound_path_sum(&path, i);
+                let element = path[i];
+                if let Some(feature) = element.feature {
+                    phi[feature] += weight * (element.one_fraction - element.zero_fraction) * value;
+                }
+            }
+            return;
+        };
+
+        let split_feature = node.data.column;
+        let mut incoming_zero_fraction = 1.0;
+        let mut incoming_one_fraction = 1.0;
+        // if the feature has already been split on undo its previous contribution to the path
+        if let Some(path_index) = path
+            .iter()
+            .position(|element| element.feature == Some(split_feature))
+        {
+            incoming_zero_fraction = path[path_index].zero_fraction;
+            incoming_one_fraction = path[path_index].one_fraction;
+            unwind_path(&mut path, path_index);
+        }
+
+        self.recurse(
+            hot_child,
+            record,
+            phi,
+            &path,
+            self.child_fraction(hot_child, node_id) * incoming_zero_fraction,
+            incoming_one_fraction,
+            Some(split_feature),
+        );
+        self.recurse(
+            cold_child,
+            record,
+            phi,
+            &path,
+            self.child_fraction(cold_child, node_id) * incoming_zero_fraction,
+            0.0,
+            Some(split_feature),
+        );
+    }
+}
+
+fn extend_path(
+    path: &mut Vec<PathElement>,
+    zero_fraction: f64,
+    one_fraction: f64,
+    feature: Option<usize>,
+) {
+    let unique_depth = path.len();
+    path.push(PathElement {
+        feature,
+        zero_fraction,
+        one_fraction,
+        weight: if unique_depth == 0 { 1.0 } else { 0.0 },
+    });
+    for i in (0..unique_depth).rev() {
+        path[i + 1].weight +=
+            one_fraction * path[i].weight * (i + 1) as f64 / (unique_depth + 1) as f64;
+        path[i].weight =
+            zero_fraction * path[i].weight * (unique_depth - i) as f64 / (unique_depth + 1) as f64;
+    }
+}
+
+fn unwind_path(path: &mut Vec<PathElement>, path_index: usize) {
+    let unique_depth = path.len() - 1;
+    let one_fraction = path[path_index].one_fraction;
+    let zero_fraction = path[path_index].zero_fraction;
+    let mut next_one_portion = path[unique_depth].weight;
+
+    for i in (0..unique_depth).rev() {
+        if one_fraction != 0.0 {
+            let weight = path[i].weight;
+            path[i].weight =
+                next_one_portion * (unique_depth + 1) as f64 / ((i + 1) as f64 * one_fraction);
+            next_one_portion = weight
+                - path[i].weight * zero_fraction * (unique_depth - i) as f64
+                    / (unique_depth + 1) as f64;
+        } else if zero_fraction != 0.0 {
+            path[i].weight = path[i].weight * (unique_depth + 1) as f64
+                / (zero_fraction * (unique_depth - i) as f64);
+        }
+    }
+
+    for i in path_index..unique_depth {
+        path[i].feature = path[i + 1].feature;
+        path[i].zero_fraction = path[i + 1].zero_fraction;
+        path[i].one_fraction = path[i + 1].one_fraction;
+    }
+    path.pop();
+}
+
+/// The total weight of the path if the element at `path_index` were unwound
+fn unwound_path_sum(path: &[PathElement], path_index: usize) -> f64 {
+    let unique_depth = path.len() - 1;
+    let one_fraction = path[path_index].one_fraction;
+    let zero_fraction = path[path_index].zero_fraction;
+    let mut next_one_portion = path[unique_depth].weight;
+    let mut total = 0.0;
+
+    for i in (0..unique_depth).rev() {
+        if one_fraction != 0.0 {
+            let weight =
+                next_one_portion * (unique_depth + 1) as f64 / ((i + 1) as f64 * one_fraction);
+            total += weight;
+            next_one_portion = path[i].weight
+                - weight * zero_fraction * (unique_depth - i) as f64 / (unique_depth + 1) as f64;
+        } else if zero_fraction != 0.0 {
+            total += path[i].weight
+                / zero_fraction
+                / ((unique_depth - i) as f64 / (unique_depth + 1) as f64);
+        }
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn node_data(
+        node_type: NodeType,
+        column: usize,
+        value: f64,
+        n_samples: usize,
+        output: isize,
+    ) -> NodeData {
+        NodeData {
+            node_type,
+            column,
+            value,
+            categories: None,
+            second_categories: None,
+            loss: 0.0,
+            impurity: 0.0,
+            n_samples,
+            weighted_n_samples: n_samples as f64,
+            default_direction: ChildType::Second,
+            encoded_prediction: output,
+            encoded_class_counts: BTreeMap::new(),
+        }
+    }
+
+    /// Expected output of the tree given only the features in `subset` are known
+    fn conditional_expectation(
+        arena: &Arena,
+        node_id: NodeId,
+        record: &[f64],
+        subset: usize,
+    ) -> f64 {
+        let node = &arena.nodes[node_id.index];
+        let (Some(first), Some(second)) = (node.first_child, node.second_child) else {
+            return node.data.encoded_prediction as f64;
+        };
+        if subset & (1 << node.data.column) != 0 {
+            let child = if record[node.data.column] > node.data.value {
+                first
+            } else {
+                second
+            };
+            conditional_expectation(arena, child, record, subset)
+        } else {
+            let cover = |id: NodeId| arena.nodes[id.index].data.n_samples as f64;
+            (cover(first) * conditional_expectation(arena, first, record, subset)
+                + cover(second) * conditional_expectation(arena, second, record, subset))
+                / node.data.n_samples as f64
+        }
+    }
+
+    fn brute_force_shap(arena: &Arena, record: &[f64]) -> Vec<f64> {
+        let n = record.len();
+        let factorial = |k: usize| (1..=k).product::<usize>() as f64;
+        let root = arena.root().unwrap();
+        (0..n)
+            .map(|feature| {
+                (0..1usize << n)
+                    .filter(|subset| subset & (1 << feature) == 0)
+                    .map(|subset| {
+                        let size = subset.count_ones() as usize;
+                        let weight = factorial(size) * factorial(n - size - 1) / factorial(n);
+                        weight
+                            * (conditional_expectation(
+                                arena,
+                                root,
+                                record,
+                                subset | (1 << feature),
+                            ) - conditional_expectation(arena, root, record, subset))
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tree_shap_matches_brute_force() {
+        // splits on column 0 twice along one path to exercise unwinding
+        let mut arena = Arena::default();
+        let root = arena.add_new_node(node_data(NodeType::Branch, 0, 5.0, 100, 0), &None, None);
+        let a = arena.add_new_node(
+            node_data(NodeType::Branch, 1, 2.0, 60, 0),
+            &Some(root),
+            Some(ChildType::First),
+        );
+        let b = arena.add_new_node(
+            node_data(NodeType::Branch, 2, 1.0, 40, 0),
+            &Some(root),
+            Some(ChildType::Second),
+        );
+        arena.add_new_node(
+            node_data(NodeType::Leaf, 0, 0.0, 25, 10),
+            &Some(a),
+            Some(ChildType::First),
+        );
+        let c = arena.add_new_node(
+            node_data(NodeType::Branch, 0, 8.0, 35, 0),
+            &Some(a),
+            Some(ChildType::Second),
+        );
+        arena.add_new_node(
+            node_data(NodeType::Leaf, 0, 0.0, 5, -4),
+            &Some(c),
+            Some(ChildType::First),
+        );
+        arena.add_new_node(
+            node_data(NodeType::Leaf, 0, 0.0, 30, 3),
+            &Some(c),
+            Some(ChildType::Second),
+        );
+        arena.add_new_node(
+            node_data(NodeType::Leaf, 0, 0.0, 10, 7),
+            &Some(b),
+            Some(ChildType::First),
+        );
+        arena.add_new_node(
+            node_data(NodeType::Leaf, 0, 0.0, 30, 1),
+            &Some(b),
+            Some(ChildType::Second),
+        );
+
+        let features = vec![
+            vec![9.0, 1.0, 0.0],
+            vec![6.0, 3.0, 2.0],
+            vec![2.0, 1.0, 2.0],
+            vec![2.0, 3.0, 0.0],
+        ];
+        let shap = tree_shap([(&arena, 1.0)], &features, |data| {
+            data.encoded_prediction as f64
+        });
+
+        for (record, phi) in features.iter().zip(&shap.values) {
+            let expected = brute_force_shap(&arena, record);
+            for (value, expected_value) in phi.iter().zip(expected) {
+                assert!((value - expected_value).abs() < 1e-9);
+            }
+            let output = conditional_expectation(&arena, root, record, 0b111);
+            assert!((shap.expected_value + phi.iter().sum::<f64>() - output).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_tree_shap_without_weight() {
+        // records of zero weight can leave nodes no weight reached
+        let mut arena = Arena::default();
+        let root = arena.add_new_node(node_data(NodeType::Branch, 0, 5.0, 0, 0), &None, None);
+        for (child_type, output) in [(ChildType::First, 2), (ChildType::Second, 4)] {
+            arena.add_new_node(
+                node_data(NodeType::Leaf, 0, 0.0, 0, output),
+                &Some(root),
+                Some(child_type),
+            );
+        }
+
+        let shap = tree_shap([(&arena, 1.0)], &vec![vec![9.0]], |data| {
+            data.encoded_prediction as f64
+        });
+        assert_eq!(shap.expected_value, 3.0);
+        assert_eq!(shap.values[0], vec![-1.0]);
+    }
+}
@@ -0,0 +1,392 @@
+use crate::data::{CsrMatrix, Dataset, Features};
+use crate::io::ReadError;
+
+use derive_builder::Builder;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Error, ErrorKind, Read, Write};
+use std::path::Path;
+
+/// How to read a LibSVM file
+///
+/// # Arguments
+/// * `n_features` - the number of feature columns, by default the largest index in the file
+/// * `zero_based` - whether feature indices start at 0 rather than the usual 1
+/// * `absent_value` - the value of features missing from a line, LibSVM treats these as 0 while
+///   XGBoost treats them as missing so set this to `NaN` to match it. Only dense features can be
+///   read with a value other than 0, see [`read_libsvm_sparse`]
+#[derive(Builder, Clone, Debug)]
+pub struct LibSvmOptions {
+    #[builder(setter(strip_option), default)]
+    pub n_features: Option<usize>,
+    #[builder(default)]
+    pub zero_based: bool,
+    #[builder(default)]
+    pub absent_value: f64,
+}
+
+impl Default for LibSvmOptions {
+    fn default() -> Self {
+        LibSvmOptionsBuilder::default()
+            .build()
+            .expect("All options have defaults")
+    }
+}
+
+/// A dataset read from a LibSVM file
+///
+/// # Arguments
+/// * `features` - the features, densified by [`read_libsvm`] and kept sparse by
+///   [`read_libsvm_sparse`]
+/// * `targets` - the label of each line
+/// * `qids` - the query id of each line if the file has them, for ranking tasks
+#[derive(Clone, Debug)]
+pub struct LibSvmData<F = Dataset> {
+    pub features: F,
+    pub targets: Vec<f64>,
+    pub qids: Option<Vec<u64>>,
+}
+
+/// Read a dataset from a LibSVM / SVMLight file
+///
+/// Each line is `<label> [qid:<qid>] <index>:<value> ...`, anything after a `#` is a comment.
+///
+/// # Arguments
+/// * `path` - the file to read
+/// * `options` - how to read the file
+pub fn read_libsvm(
+    path: impl AsRef<Path>,
+    options: &LibSvmOptions,
+) -> Result<LibSvmData, ReadError> {
+    read_libsvm_from_reader(File::open(path)?, options)
+}
+
+/// Read a dataset in LibSVM format, see [`read_libsvm`]
+pub fn read_libsvm_from_reader<R: Read>(
+    reader: R,
+    options: &LibSvmOptions,
+) -> Result<LibSvmData, ReadError> {
+    let lines = parse_libsvm(reader, options)?;
+    let mut columns = vec![vec![options.absent_value; lines.entries.len()]; lines.n_features];
+    for (row, row_entries) in lines.entries.into_iter().enumerate() {
+        for (column, value) in row_entries {
+            columns[column][row] = value;
+        }
+    }
+    Ok(LibSvmData {
+        features: Dataset::from_columns(columns),
+        targets: lines.targets,
+        qids: lines.qids,
+    })
+}
+
+/// Read a dataset from a LibSVM / SVMLight file without densifying it, features missing from a
+/// line aren't stored
+///
+/// # Arguments
+/// * `path` - the file to read
+/// * `options` - how to read the file
+///
+/// # Errors
+/// [`ReadError::SparseAbsentValue`] if `options.absent_value` isn't 0, sparse matrices can't hold
+/// any other value for the features they don't store
+pub fn read_libsvm_sparse(
+    path: impl AsRef<Path>,
+    options: &LibSvmOptions,
+) -> Result<LibSvmData<CsrMatrix>, ReadError> {
+    read_libsvm_sparse_from_reader(File::open(path)?, options)
+}
+
+/// Read a dataset in LibSVM format without densifying it, see [`read_libsvm_sparse`]
+pub fn read_libsvm_sparse_from_reader<R: Read>(
+    reader: R,
+    options: &LibSvmOptions,
+) -> Result<LibSvmData<CsrMatrix>, ReadError> {
+    if options.absent_value != 0.0 {
+        return Err(ReadError::SparseAbsentValue(options.absent_value));
+    }
+    let lines = parse_libsvm(reader, options)?;
+    let n_rows = lines.entries.len();
+    let mut indptr = vec![0];
+    let mut indices = vec![];
+    let mut values = vec![];
+    for mut row_entries in lines.entries {
+        // when a feature is repeated on a line the last value wins, as when densifying
+        row_entries.reverse();
+        row_entries.sort_by_key(|(column, _)| *column);
+        row_entries.dedup_by_key(|(column, _)| *column);
+        for (column, value) in row_entries {
+            indices.push(column);
+            values.push(value);
+        }
+        indptr.push(indices.len());
+    }
+    Ok(LibSvmData {
+        features: CsrMatrix::new(n_rows, lines.n_features, indptr, indices, values),
+        targets: lines.targets,
+        qids: lines.qids,
+    })
+}
+
+/// The lines of a LibSVM file
+///
+/// # Arguments
+/// * `targets` - the label of each line
+/// * `qids` - the query id of each line if the file has them
+/// * `entries` - the column and value of each feature on each line, in the order they appear
+/// * `n_features` - the number of feature columns
+struct LibSvmLines {
+    targets: Vec<f64>,
+    qids: Option<Vec<u64>>,
+    entries: Vec<Vec<(usize, f64)>>,
+    n_features: usize,
+}
+
+fn parse_libsvm<R: Read>(reader: R, options: &LibSvmOptions) -> Result<LibSvmLines, ReadError> {
+    let mut targets = vec![];
+    let mut qids = vec![];
+    let mut entries: Vec<Vec<(usize, f64)>> = vec![];
+    // blank and comment lines are skipped so records aren't numbered the same as lines
+    let mut line_numbers = vec![];
+    let mut n_features = 0;
+
+    for (line_index, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line?;
+        let line_number = line_index as u64 + 1;
+        let parse_error = |message: String| ReadError::Parse {
+            line: line_number,
+            message,
+        };
+        let content = line.split('#').next().unwrap_or_default().trim();
+        let mut tokens = content.split_whitespace();
+        let Some(label) = tokens.next() else {
+            continue;
+        };
+        targets.push(
+            label
+                .parse::<f64>()
+                .map_err(|_| parse_error(format!("invalid label {label}")))?,
+        );
+        line_numbers.push(line_number);
+
+        let mut row = vec![];
+        for token in tokens {
+            let (key, value) = token
+                .split_once(':')
+                .ok_or_else(|| parse_error(format!("expected index:value, found {token}")))?;
+            if key == "qid" {
+                let qid = value
+                    .parse::<u64>()
+                    .map_err(|_| parse_error(format!("invalid qid {value}")))?;
+                qids.resize(targets.len() - 1, None);
+                qids.push(Some(qid));
+                continue;
+            }
+            let index = key
+                .parse::<usize>()
+                .map_err(|_| parse_error(format!("invalid index {key}")))?;
+            let column = match options.zero_based {
+                true => index,
+                false => index
+                    .checked_sub(1)
+                    .ok_or_else(|| parse_error("indices start at 1".to_string()))?,
+            };
+            let value = value
+                .parse::<f64>()
+                .map_err(|_| parse_error(format!("invalid value {value}")))?;
+            n_features = n_features.max(column + 1);
+            row.push((column, value));
+        }
+        entries.push(row);
+    }
+
+    let n_features = match options.n_features {
+        Some(expected) if expected < n_features => {
+            return Err(ReadError::FeatureCount {
+                expected,
+                found: n_features,
+            })
+        }
+        Some(n) => n,
+        None => n_features,
+    };
+    let qids = match qids.is_empty() {
+        true => None,
+        false => {
+            qids.resize(targets.len(), None);
+            Some(
+                qids.into_iter()
+                    .zip(line_numbers)
+                    .map(|(qid, line)| {
+                        qid.ok_or_else(|| ReadError::Parse {
+                            line,
+                            message: "qid is missing".to_string(),
+                        })
+                    })
+                    .collect::<Result<_, _>>()?,
+            )
+        }
+    };
+
+    Ok(LibSvmLines {
+        targets,
+        qids,
+        entries,
+        n_features,
+    })
+}
+
+/// Write a dataset to a LibSVM / SVMLight file
+///
+/// Indices are written starting at 1, zeros and missing values are left out.
+///
+/// # Arguments
+/// * `path` - the file to write
+/// * `features` - the features to write
+/// * `targets` - the label of each record
+/// * `qids` - the query id of each record, for ranking tasks
+///
+/// # Errors
+/// if the file can't be written, or with [`ErrorKind::InvalidInput`] if `targets` or `qids` don't
+/// have one entry per record
+pub fn write_libsvm<T: Display>(
+    path: impl AsRef<Path>,
+    features: &dyn Features,
+    targets: &[T],
+    qids: Option<&[u64]>,
+) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_libsvm_to_writer(&mut writer, features, targets, qids)?;
+    writer.flush()
+}
+
+/// Write a dataset in LibSVM format, see [`write_libsvm`]
+pub fn write_libsvm_to_writer<W: Write, T: Display>(
+    writer: &mut W,
+    features: &dyn Features,
+    targets: &[T],
+    qids: Option<&[u64]>,
+) -> std::io::Result<()> {
+    if features.n_rows() != targets.len() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "{} records but {} targets",
+                features.n_rows(),
+                targets.len()
+            ),
+        ));
+    }
+    if let Some(qids) = qids.filter(|qids| qids.len() != targets.len()) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} qids but {} targets", qids.len(), targets.len()),
+        ));
+    }
+
+    for (row, target) in targets.iter().enumerate() {
+        write!(writer, "{target}")?;
+        if let Some(qids) = qids {
+            write!(writer, " qid:{}", qids[row])?;
+        }
+        for (column, value) in features.row(row).iter().enumerate() {
+            if *value != 0.0 && !value.is_nan() {
+                write!(writer, " {}:{value}", column + 1)?;
+            }
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_libsvm_round_trip() {
+        let file = "\
+# a comment
+1 qid:3 1:0.5 3:2 # trailing comment
+
+-1 qid:3 2:1.5
++1 qid:7 4:-1
+";
+        let data = read_libsvm_from_reader(file.as_bytes(), &Default::default()).unwrap();
+        assert_eq!(data.targets, [1.0, -1.0, 1.0]);
+        assert_eq!(data.qids, Some(vec![3, 3, 7]));
+        assert_eq!(data.features.shape(), (3, 4));
+        assert_eq!(data.features.row(0), [0.5, 0.0, 2.0, 0.0]);
+        assert_eq!(data.features.row(2), [0.0, 0.0, 0.0, -1.0]);
+
+        let mut written = vec![];
+        write_libsvm_to_writer(
+            &mut written,
+            &data.features,
+            &data.targets,
+            data.qids.as_deref(),
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(written.clone()).unwrap(),
+            "1 qid:3 1:0.5 3:2\n-1 qid:3 2:1.5\n1 qid:7 4:-1\n"
+        );
+
+        let options = LibSvmOptionsBuilder::default()
+            .n_features(5)
+            .absent_value(f64::NAN)
+            .build()
+            .unwrap();
+        let data = read_libsvm_from_reader(written.as_slice(), &options).unwrap();
+        assert_eq!(data.features.shape(), (3, 5));
+        assert!(data.features.value(0, 1).is_nan());
+
+        let data = read_libsvm_from_reader("1 0:1".as_bytes(), &Default::default());
+        assert!(matches!(data, Err(ReadError::Parse { line: 1, .. })));
+    }
+
+    #[test]
+    fn test_libsvm_sparse() {
+        let file = "1 3:2 1:0.5 3:4\n-1\n0 2:1.5\n";
+        let data = read_libsvm_sparse_from_reader(file.as_bytes(), &Default::default()).unwrap();
+        assert_eq!(data.targets, [1.0, -1.0, 0.0]);
+        assert_eq!(data.features.shape(), (3, 3));
+        assert_eq!(data.features.nnz(), 3);
+        assert_eq!(data.features.row_entries(0), (&[0, 2][..], &[0.5, 4.0][..]));
+        let dense = read_libsvm_from_reader(file.as_bytes(), &Default::default()).unwrap();
+        assert_eq!(data.features.to_dataset().as_ref(), &dense.features);
+
+        let options = LibSvmOptionsBuilder::default()
+            .absent_value(f64::NAN)
+            .build()
+            .unwrap();
+        let data = read_libsvm_sparse_from_reader(file.as_bytes(), &options);
+        assert!(matches!(data, Err(ReadError::SparseAbsentValue(value)) if value.is_nan()));
+    }
+
+    #[test]
+    fn test_libsvm_errors() {
+        let options = LibSvmOptionsBuilder::default()
+            .n_features(2)
+            .build()
+            .unwrap();
+        let data = read_libsvm_from_reader("1 3:1".as_bytes(), &options);
+        assert!(matches!(
+            data,
+            Err(ReadError::FeatureCount {
+                expected: 2,
+                found: 3
+            })
+        ));
+        let file = "# comment\n\n1 qid:1 1:1\n0 1:2\n";
+        let data = read_libsvm_from_reader(file.as_bytes(), &Default::default());
+        assert!(matches!(data, Err(ReadError::Parse { line: 4, .. })));
+
+        let features = vec![vec![1.0], vec![2.0]];
+        let error = write_libsvm_to_writer(&mut vec![], &features, &[1], None).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        let error =
+            write_libsvm_to_writer(&mut vec![], &features, &[1, 0], Some(&[1])).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,100 @@
+mod libsvm;
+mod read_csv;
+#[cfg(feature = "arrow")]
+mod record_batch;
+pub use libsvm::*;
+pub use read_csv::*;
+#[cfg(feature = "arrow")]
+pub use record_batch::*;
+
+use std::fmt::{Display, Formatter};
+
+/// An error reading a dataset
+#[derive(Debug)]
+pub enum ReadError {
+    /// The file couldn't be read
+    Io(std::io::Error),
+    /// The file isn't valid CSV
+    Csv(csv::Error),
+    /// A requested column doesn't exist
+    MissingColumn(String),
+    /// A value couldn't be interpreted
+    Parse { line: u64, message: String },
+    /// The file has more features than expected
+    FeatureCount { expected: usize, found: usize },
+    /// Sparse features were requested with a value other than 0 for absent features
+    SparseAbsentValue(f64),
+    /// A column's type can't be used as a feature
+    UnsupportedColumn(String),
+    /// Arrow data couldn't be read
+    #[cfg(feature = "arrow")]
+    Arrow(arrow::error::ArrowError),
+    /// The file isn't valid Parquet
+    #[cfg(feature = "arrow")]
+    Parquet(parquet::errors::ParquetError),
+}
+
+impl Display for ReadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadError::Io(error) => write!(f, "Couldn't read dataset: {error}"),
+            ReadError::Csv(error) => write!(f, "Couldn't parse CSV: {error}"),
+            ReadError::MissingColumn(column) => write!(f, "Column {column} doesn't exist"),
+            ReadError::Parse { line, message } => write!(f, "Line {line}: {message}"),
+            ReadError::FeatureCount { expected, found } => {
+                write!(f, "Found {found} features, expected {expected}")
+            }
+            ReadError::SparseAbsentValue(value) => write!(
+                f,
+                "Sparse features can only be read with an absent value of 0, got {value}"
+            ),
+            ReadError::UnsupportedColumn(column) => {
+                write!(f, "Column {column} isn't numeric or categorical")
+            }
+            #[cfg(feature = "arrow")]
+            ReadError::Arrow(error) => write!(f, "Couldn't read Arrow data: {error}"),
+            #[cfg(feature = "arrow")]
+            ReadError::Parquet(error) => write!(f, "Couldn't read Parquet: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadError::Io(error) => Some(error),
+            ReadError::Csv(error) => Some(error),
+            #[cfg(feature = "arrow")]
+            ReadError::Arrow(error) => Some(error),
+            #[cfg(feature = "arrow")]
+            ReadError::Parquet(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ReadError {
+    fn from(error: std::io::Error) -> Self {
+        ReadError::Io(error)
+    }
+}
+
+impl From<csv::Error> for ReadError {
+    fn from(error: csv::Error) -> Self {
+        ReadError::Csv(error)
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl From<arrow::error::ArrowError> for ReadError {
+    fn from(error: arrow::error::ArrowError) -> Self {
+        ReadError::Arrow(error)
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl From<parquet::errors::ParquetError> for ReadError {
+    fn from(error: parquet::errors::ParquetError) -> Self {
+        ReadError::Parquet(error)
+    }
+}
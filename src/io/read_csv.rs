@@ -0,0 +1,268 @@
+use crate::data::Dataset;
+use crate::io::ReadError;
+
+use csv::{ReaderBuilder, StringRecord, Trim};
+use derive_builder::Builder;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// A column of a CSV file, by position or by header
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Column {
+    Index(usize),
+    Name(String),
+}
+
+impl From<usize> for Column {
+    fn from(index: usize) -> Self {
+        Column::Index(index)
+    }
+}
+
+impl From<&str> for Column {
+    fn from(name: &str) -> Self {
+        Column::Name(name.to_string())
+    }
+}
+
+/// How to read a CSV file
+///
+/// # Arguments
+/// * `delimiter` - the byte separating fields
+/// * `has_headers` - whether the first row holds column names
+/// * `target_column` - the column holding the targets, if any
+/// * `missing_values` - fields read as missing values
+/// * `categorical_columns` - columns to read as categories even if they're numeric, columns that
+///   aren't numeric are always read as categories
+#[derive(Builder, Clone, Debug)]
+pub struct CsvOptions {
+    #[builder(default = "b','")]
+    pub delimiter: u8,
+    #[builder(default = "true")]
+    pub has_headers: bool,
+    #[builder(setter(into, strip_option), default)]
+    pub target_column: Option<Column>,
+    #[builder(
+        default = r#"["", "NA", "N/A", "NaN", "nan", "null", "?"].map(String::from).to_vec()"#
+    )]
+    pub missing_values: Vec<String>,
+    #[builder(default)]
+    pub categorical_columns: Vec<Column>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptionsBuilder::default()
+            .build()
+            .expect("All options have defaults")
+    }
+}
+
+/// A dataset read from a CSV file
+///
+/// # Arguments
+/// * `features` - every column other than the target, named by the headers if there are any
+/// * `targets` - the values of the target column, if one was chosen
+/// * `categorical_features` - the columns of `features` holding categories, these can be passed
+///   straight to [`crate::tree::ClassificationTreeBuilder::categorical_features`]
+/// * `categories` - for each categorical column the category each code represents
+#[derive(Clone, Debug)]
+pub struct CsvData {
+    pub features: Dataset,
+    pub targets: Option<Vec<String>>,
+    pub categorical_features: Vec<usize>,
+    pub categories: BTreeMap<usize, Vec<String>>,
+}
+
+/// Read a dataset from a CSV file
+///
+/// Numeric columns are read as they are and any other column as categories, encoded as
+/// `0..n_categories` in sorted order. Missing values are read as `NaN`.
+///
+/// # Arguments
+/// * `path` - the file to read
+/// * `options` - how to read the file
+pub fn read_csv(path: impl AsRef<Path>, options: &CsvOptions) -> Result<CsvData, ReadError> {
+    read_csv_from_reader(File::open(path)?, options)
+}
+
+/// Read a dataset from CSV, see [`read_csv`]
+pub fn read_csv_from_reader<R: Read>(
+    reader: R,
+    options: &CsvOptions,
+) -> Result<CsvData, ReadError> {
+    let mut reader = ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .has_headers(options.has_headers)
+        .trim(Trim::All)
+        .from_reader(reader);
+    let headers = match options.has_headers {
+        true => Some(reader.headers()?.clone()),
+        false => None,
+    };
+    let records = reader.records().collect::<Result<Vec<_>, _>>()?;
+    let n_columns = match (&headers, records.first()) {
+        (Some(headers), _) => headers.len(),
+        (None, Some(record)) => record.len(),
+        (None, None) => 0,
+    };
+
+    let resolve = |column: &Column| -> Result<usize, ReadError> {
+        let index = match column {
+            Column::Index(index) => Some(*index),
+            Column::Name(name) => headers
+                .as_ref()
+                .and_then(|headers| headers.iter().position(|header| header == name)),
+        };
+        index
+            .filter(|index| *index < n_columns)
+            .ok_or_else(|| ReadError::MissingColumn(format!("{column:?}")))
+    };
+    let target_column = options.target_column.as_ref().map(resolve).transpose()?;
+    let categorical_columns = options
+        .categorical_columns
+        .iter()
+        .map(resolve)
+        .collect::<Result<BTreeSet<_>, _>>()?;
+    let is_missing = |field: &str| {
+        options
+            .missing_values
+            .iter()
+            .any(|missing| missing == field)
+    };
+
+    let targets = target_column
+        .map(|column| {
+            records
+                .iter()
+                .map(|record| {
+                    let field = &record[column];
+                    if is_missing(field) {
+                        Err(ReadError::Parse {
+                            line: line(record),
+                            message: "target is missing".to_string(),
+                        })
+                    } else {
+                        Ok(field.to_string())
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()?;
+
+    let mut columns = vec![];
+    let mut feature_names = vec![];
+    let mut categorical_features = vec![];
+    let mut categories = BTreeMap::new();
+    for column in (0..n_columns).filter(|column| Some(*column) != target_column) {
+        let fields = records
+            .iter()
+            .map(|record| &record[column])
+            .collect::<Vec<_>>();
+        let numeric = fields
+            .iter()
+            .filter(|field| !is_missing(field))
+            .map(|field| field.parse::<f64>())
+            .collect::<Result<Vec<_>, _>>();
+
+        let values = match numeric {
+            Ok(_) if !categorical_columns.contains(&column) => fields
+                .iter()
+                .map(|field| match is_missing(field) {
+                    true => f64::NAN,
+                    false => field.parse().expect("Checked the field is numeric"),
+                })
+                .collect(),
+            _ => {
+                let column_categories = fields
+                    .iter()
+                    .filter(|field| !is_missing(field))
+                    .map(|field| field.to_string())
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .collect::<Vec<_>>();
+                let values = fields
+                    .iter()
+                    .map(|field| match is_missing(field) {
+                        true => f64::NAN,
+                        false => column_categories
+                            .binary_search_by(|category| category.as_str().cmp(field))
+                            .expect("Every category was collected")
+                            as f64,
+                    })
+                    .collect();
+                categorical_features.push(columns.len());
+                categories.insert(columns.len(), column_categories);
+                values
+            }
+        };
+        columns.push(values);
+        if let Some(headers) = &headers {
+            feature_names.push(headers[column].to_string());
+        }
+    }
+
+    let mut features = Dataset::from_columns(columns);
+    if headers.is_some() {
+        features = features.with_feature_names(feature_names);
+    }
+    Ok(CsvData {
+        features,
+        targets,
+        categorical_features,
+        categories,
+    })
+}
+
+fn line(record: &StringRecord) -> u64 {
+    record
+        .position()
+        .map(|position| position.line())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_csv() {
+        let csv = "\
+ph;grade;taste;\"notes; free text\"
+6.6;high;1;\"fresh, creamy\"
+NA;low;0;sour
+4.5;;1;\"\"\"odd\"\"\"
+";
+        let options = CsvOptionsBuilder::default()
+            .delimiter(b';')
+            .target_column("grade")
+            .categorical_columns(vec![Column::Index(2)])
+            .build()
+            .unwrap();
+        let data = read_csv_from_reader(csv.as_bytes(), &options);
+        assert!(matches!(data, Err(ReadError::Parse { line: 4, .. })));
+
+        let csv = csv.replace(";;", ";medium;");
+        let data = read_csv_from_reader(csv.as_bytes(), &options).unwrap();
+        assert_eq!(data.targets.unwrap(), ["high", "low", "medium"]);
+        assert_eq!(
+            data.features.feature_names().unwrap(),
+            ["ph", "taste", "notes; free text"]
+        );
+        assert_eq!(data.features.value(0, 0), 6.6);
+        assert!(data.features.value(1, 0).is_nan());
+        assert_eq!(data.categorical_features, [1, 2]);
+        assert_eq!(data.categories[&2], ["\"odd\"", "fresh, creamy", "sour"]);
+        assert_eq!(data.features.column(2), [1.0, 2.0, 0.0]);
+
+        let options = CsvOptionsBuilder::default()
+            .delimiter(b';')
+            .target_column("missing")
+            .build()
+            .unwrap();
+        let data = read_csv_from_reader(csv.as_bytes(), &options);
+        assert!(matches!(data, Err(ReadError::MissingColumn(_))));
+    }
+}
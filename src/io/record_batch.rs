@@ -0,0 +1,270 @@
+use crate::data::Dataset;
+use crate::io::ReadError;
+
+use arrow::array::{Array, ArrayRef, AsArray};
+use arrow::compute::{cast, cast_with_options, concat, CastOptions};
+use arrow::datatypes::{DataType, Float64Type};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::path::Path;
+
+/// A dataset read from Arrow record batches
+///
+/// # Arguments
+/// * `features` - every column other than the target, named by the schema
+/// * `targets` - the target column, if one was chosen
+/// * `categorical_features` - the columns of `features` that were dictionary encoded, these can
+///   be passed straight to [`crate::tree::ClassificationTreeBuilder::categorical_features`]
+/// * `categories` - for each categorical column the category each code represents
+#[derive(Clone, Debug)]
+pub struct ArrowData {
+    pub features: Dataset,
+    pub targets: Option<ArrayRef>,
+    pub categorical_features: Vec<usize>,
+    pub categories: BTreeMap<usize, Vec<String>>,
+}
+
+/// Read a dataset from a Parquet file, see [`from_record_batches`]
+///
+/// # Arguments
+/// * `path` - the file to read
+/// * `target_column` - the name of the column holding the targets, if any
+pub fn read_parquet(
+    path: impl AsRef<Path>,
+    target_column: Option<&str>,
+) -> Result<ArrowData, ReadError> {
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?.build()?;
+    let batches = reader.collect::<Result<Vec<_>, _>>()?;
+    from_record_batches(&batches, target_column)
+}
+
+/// Read a dataset from a single Arrow record batch, see [`from_record_batches`]
+pub fn from_record_batch(
+    batch: &RecordBatch,
+    target_column: Option<&str>,
+) -> Result<ArrowData, ReadError> {
+    from_record_batches(std::slice::from_ref(batch), target_column)
+}
+
+/// Read a dataset from Arrow record batches sharing a schema
+///
+/// Numeric and boolean columns become features and dictionary encoded columns become
+/// categorical features, encoded in the order their categories are first seen. Values are
+/// copied straight from the Arrow buffers into the dataset and nulls are read as missing values.
+///
+/// # Arguments
+/// * `batches` - the record batches, in order
+/// * `target_column` - the name of the column holding the targets, if any
+pub fn from_record_batches(
+    batches: &[RecordBatch],
+    target_column: Option<&str>,
+) -> Result<ArrowData, ReadError> {
+    let Some(schema) = batches.first().map(|batch| batch.schema()) else {
+        return Ok(ArrowData {
+            features: Dataset::default(),
+            targets: None,
+            categorical_features: vec![],
+            categories: BTreeMap::new(),
+        });
+    };
+    let target_index = target_column
+        .map(|name| {
+            schema
+                .index_of(name)
+                .map_err(|_| ReadError::MissingColumn(name.to_string()))
+        })
+        .transpose()?;
+    let n_rows = batches.iter().map(|batch| batch.num_rows()).sum::<usize>();
+    let feature_columns = (0..schema.fields().len())
+        .filter(|column| Some(*column) != target_index)
+        .collect::<Vec<_>>();
+
+    let mut values = Vec::with_capacity(n_rows * feature_columns.len());
+    let mut feature_names = vec![];
+    let mut categorical_features = vec![];
+    let mut categories = BTreeMap::new();
+    for (feature, column) in feature_columns.into_iter().enumerate() {
+        let field = schema.field(column);
+        match field.data_type() {
+            DataType::Dictionary(_, _) => {
+                let column_categories = extend_categorical(&mut values, batches, column)?;
+                categorical_features.push(feature);
+                categories.insert(feature, column_categories);
+            }
+            data_type if data_type.is_numeric() || *data_type == DataType::Boolean => {
+                extend_numeric(&mut values, batches, column)?
+            }
+            _ => return Err(ReadError::UnsupportedColumn(field.name().clone())),
+        }
+        feature_names.push(field.name().clone());
+    }
+
+    let targets = target_index
+        .map(|column| {
+            let arrays = batches
+                .iter()
+                .map(|batch| batch.column(column).as_ref())
+                .collect::<Vec<_>>();
+            concat(&arrays)
+        })
+        .transpose()?;
+
+    Ok(ArrowData {
+        features: Dataset::from_column_major(values, n_rows, feature_names.len())
+            .with_feature_names(feature_names),
+        targets,
+        categorical_features,
+        categories,
+    })
+}
+
+/// Append a numeric column to column major values, nulls become `NaN`
+fn extend_numeric(
+    values: &mut Vec<f64>,
+    batches: &[RecordBatch],
+    column: usize,
+) -> Result<(), ReadError> {
+    let options = CastOptions {
+        safe: false,
+        ..Default::default()
+    };
+    for batch in batches {
+        let array = batch.column(column);
+        match array.data_type() {
+            DataType::Float64 => extend_float64(values, array.as_primitive::<Float64Type>()),
+            _ => {
+                let array = cast_with_options(array, &DataType::Float64, &options)?;
+                extend_float64(values, array.as_primitive::<Float64Type>())
+            }
+        }
+    }
+    Ok(())
+}
+
+fn extend_float64(values: &mut Vec<f64>, array: &arrow::array::Float64Array) {
+    match array.null_count() {
+        0 => values.extend_from_slice(array.values()),
+        _ => values.extend(array.iter().map(|value| value.unwrap_or(f64::NAN))),
+    }
+}
+
+/// Append a dictionary encoded column to column major values as category codes
+///
+/// Each batch can have its own dictionary so codes are mapped to one shared set of categories.
+///
+/// # Returns
+/// the category each code represents
+fn extend_categorical(
+    values: &mut Vec<f64>,
+    batches: &[RecordBatch],
+    column: usize,
+) -> Result<Vec<String>, ReadError> {
+    let mut categories = vec![];
+    let mut codes = HashMap::new();
+    for batch in batches {
+        let array = batch.column(column);
+        let dictionary = array.as_any_dictionary_opt().ok_or_else(|| {
+            ReadError::UnsupportedColumn(batch.schema_ref().field(column).name().clone())
+        })?;
+        let dictionary_values = cast(dictionary.values(), &DataType::Utf8)?;
+        let dictionary_values = dictionary_values.as_string::<i32>();
+        // only categories that are used get a code, dictionaries can hold unused values
+        let mut batch_codes = vec![None; dictionary_values.len()];
+        for (row, key) in dictionary.normalized_keys().into_iter().enumerate() {
+            if array.is_null(row) || dictionary_values.is_null(key) {
+                values.push(f64::NAN);
+                continue;
+            }
+            let code = *batch_codes[key].get_or_insert_with(|| {
+                let category = dictionary_values.value(key);
+                *codes.entry(category.to_string()).or_insert_with(|| {
+                    categories.push(category.to_string());
+                    categories.len() - 1
+                }) as f64
+            });
+            values.push(code);
+        }
+    }
+    Ok(categories)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{DictionaryArray, Float64Array, Int32Array, StringArray};
+    use arrow::datatypes::Int32Type;
+    use parquet::arrow::ArrowWriter;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_read_parquet() {
+        let batch = |ph: Vec<Option<f64>>, taste: Vec<i32>, colour: Vec<Option<&str>>| {
+            let grade = vec!["high"; ph.len()];
+            RecordBatch::try_from_iter([
+                ("ph", Arc::new(Float64Array::from(ph)) as ArrayRef),
+                ("taste", Arc::new(Int32Array::from(taste)) as ArrayRef),
+                (
+                    "colour",
+                    Arc::new(colour.into_iter().collect::<DictionaryArray<Int32Type>>())
+                        as ArrayRef,
+                ),
+                ("grade", Arc::new(StringArray::from(grade)) as ArrayRef),
+            ])
+            .unwrap()
+        };
+        let batches = [
+            batch(vec![Some(6.6), None], vec![1, 0], vec![Some("white"), None]),
+            batch(vec![Some(4.5)], vec![1], vec![Some("cream")]),
+            batch(vec![Some(9.0)], vec![0], vec![Some("cream")]),
+        ];
+
+        let path = std::env::temp_dir().join(format!("timber-{}.parquet", std::process::id()));
+        let mut writer =
+            ArrowWriter::try_new(File::create(&path).unwrap(), batches[0].schema(), None).unwrap();
+        for batch in &batches {
+            writer.write(batch).unwrap();
+        }
+        writer.close().unwrap();
+        let from_parquet = read_parquet(&path, Some("grade")).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let from_batches = from_record_batches(&batches, Some("grade")).unwrap();
+
+        for data in [from_parquet, from_batches] {
+            assert_eq!(data.features.shape(), (4, 3));
+            assert_eq!(
+                data.features.feature_names().unwrap(),
+                ["ph", "taste", "colour"]
+            );
+            assert_eq!(data.features.value(0, 0), 6.6);
+            assert!(data.features.value(1, 0).is_nan());
+            assert_eq!(data.features.column(1), [1.0, 0.0, 1.0, 0.0]);
+            assert_eq!(data.categorical_features, [2]);
+            assert_eq!(data.categories[&2], ["white", "cream"]);
+            assert!(data.features.value(1, 2).is_nan());
+            assert_eq!(data.features.value(3, 2), 1.0);
+            assert_eq!(data.targets.unwrap().len(), 4);
+        }
+
+        let data = from_record_batch(&batches[0], Some("missing"));
+        assert!(matches!(data, Err(ReadError::MissingColumn(_))));
+
+        // a batch whose categorical column isn't dictionary encoded
+        let plain = RecordBatch::try_from_iter([
+            ("ph", Arc::new(Float64Array::from(vec![7.0])) as ArrayRef),
+            ("taste", Arc::new(Int32Array::from(vec![1])) as ArrayRef),
+            (
+                "colour",
+                Arc::new(StringArray::from(vec!["white"])) as ArrayRef,
+            ),
+            (
+                "grade",
+                Arc::new(StringArray::from(vec!["low"])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+        let data = from_record_batches(&[batches[0].clone(), plain], Some("grade"));
+        assert!(matches!(data, Err(ReadError::UnsupportedColumn(column)) if column == "colour"));
+    }
+}
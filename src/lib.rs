@@ -1,26 +1,60 @@
+pub mod data;
+pub mod inspection;
+pub mod io;
 pub mod loss_functions;
 pub mod tree;
 pub mod metrics;
+pub mod model_selection;
+pub mod preprocessing;
+#[cfg(test)]
 mod utils;
 
 use async_trait::async_trait;
+use data::Features;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// A class label models can be fit on, such as an integer, string or enum
+pub trait Label: Eq + Hash + Clone + Ord + Debug + Send + Sync {}
+
+impl<L> Label for L where L: Eq + Hash + Clone + Ord + Debug + Send + Sync {}
 
 #[async_trait]
-pub trait Model {
-    async fn fit(&mut self, features: &[Vec<f64>], targets: &[isize]);
+pub trait Model<L: Label = isize> {
+    async fn fit(&mut self, features: &dyn Features, targets: &[L]);
 
-    async fn predict(&self, features: &[Vec<f64>]) -> Vec<isize>;
+    async fn predict(&self, features: &dyn Features) -> Vec<L>;
 
-    async fn predict_proba(&self, features: &[Vec<f64>]) -> Vec<(f64, f64)>;
+    /// The probability of each class for each record, in the order of [`Model::classes`]
+    async fn predict_proba(&self, features: &dyn Features) -> Vec<Vec<f64>>;
 
-    async fn score(&self, features: &[Vec<f64>], targets: &[isize], metric: metrics::Metric) -> f64;
+    /// The classes the model was fit on, sorted
+    fn classes(&self) -> &[L];
+
+    /// Score the model with a [`metrics::Metric`] or any other [`metrics::Scorer`]
+    async fn score<S>(&self, features: &dyn Features, targets: &[L], scorer: S) -> f64
+    where
+        S: metrics::Scorer<L>,
+        Self: Sized + Sync,
+    {
+        scorer.score(self, features, targets).await
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::tree::ClassificationTreeBuilder;
-    use crate::utils::load_milk_train_dataset;
-    use crate::Model;
+    use crate::tree::{
+        CategorySet, ChildType, ClassWeight, ClassificationTree, ClassificationTreeBuilder,
+        CostMatrix, CostMatrixError, NodeType,
+    };
+    use std::collections::BTreeMap;
+    use crate::utils::{load_milk_test_dataset, load_milk_train_dataset};
+    use crate::data::{CscMatrix, CsrMatrix, Dataset, Features};
+    use crate::inspection::permutation_importance;
+    use crate::model_selection::{cross_val_score, StratifiedKFold};
+    use crate::metrics::{make_scorer, precision, recall, Average, Metric, Scorer};
+    use async_trait::async_trait;
+    use crate::{metrics, Model};
     use std::time::Instant;
 
     #[tokio::test]
@@ -30,7 +64,7 @@ mod tests {
         let mut model = ClassificationTreeBuilder::default().build().unwrap();
 
         model.fit(&features, &targets).await;
-        let e = s.elapsed().as_secs_f32();
+        let _e = s.elapsed().as_secs_f32();
 
         let test_feature = vec![
             vec![6.6, 40.0, 1.0, 0.0, 1.0, 1.0, 255.0],
@@ -47,6 +81,608 @@ mod tests {
             vec![6.8, 40.0, 1.0, 0.0, 1.0, 0.0, 245.0]];
 
         let pred = model.predict(&test_feature).await;
-        dbg!(pred);
+        assert_eq!(pred.len(), test_feature.len());
+    }
+
+    #[tokio::test]
+    async fn test_prune_with_validation() {
+        let (features, targets) = load_milk_train_dataset();
+        let (test_features, test_targets) = load_milk_test_dataset();
+        let mut model = ClassificationTreeBuilder::default().build().unwrap();
+        model.fit(&features, &targets).await;
+
+        let report = model.prune_with_validation(&test_features, &test_targets).await;
+        assert!(report.score_after >= report.score_before);
+        assert_eq!(
+            model.score(&test_features, &test_targets, Metric::Accuracy).await,
+            report.score_after
+        );
+        for node_id in report.collapsed_nodes {
+            let node = &model.nodes.nodes[node_id.index];
+            assert!(node.first_child.is_none() && node.second_child.is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_feature_importances() {
+        let (features, targets) = load_milk_train_dataset();
+        let mut model = ClassificationTreeBuilder::default().build().unwrap();
+        model.fit(&features, &targets).await;
+
+        let importances = model.feature_importances();
+        assert_eq!(importances.len(), features[0].len());
+        assert!(importances.iter().all(|importance| *importance >= 0.0));
+        assert!((importances.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_permutation_importance() {
+        let (features, targets) = load_milk_train_dataset();
+        let (test_features, test_targets) = load_milk_test_dataset();
+        let mut model = ClassificationTreeBuilder::default().build().unwrap();
+        model.fit(&features, &targets).await;
+
+        let importance =
+            permutation_importance(&model, &test_features, &test_targets, Metric::Accuracy, 3, 42)
+                .await;
+        assert_eq!(importance.importances_mean.len(), features[0].len());
+        assert!(importance.importances.iter().all(|drops| drops.len() == 3));
+        assert!(importance.importances_std.iter().all(|std| *std >= 0.0));
+    }
+
+    #[tokio::test]
+    async fn test_apply_and_decision_path() {
+        let (features, targets) = load_milk_train_dataset();
+        let (test_features, _) = load_milk_test_dataset();
+        // labels that differ from their encodings
+        let targets = targets.iter().map(|target| target + 10).collect::<Vec<_>>();
+        let mut model = ClassificationTreeBuilder::default().build().unwrap();
+        model.fit(&features, &targets).await;
+
+        let leaves = model.apply(&test_features).await;
+        let paths = model.decision_path(&test_features).await;
+        let preds = model.predict(&test_features).await;
+        for ((leaf, path), pred) in leaves.iter().zip(&paths).zip(preds) {
+            assert_eq!(*leaf, path.leaf);
+            let prediction = model.nodes.nodes[leaf.index].data.encoded_prediction;
+            assert_ne!(prediction, pred);
+            assert_eq!(*model.label_encoder().decode(prediction), pred);
+            assert_eq!(path.nodes()[0], model.nodes.root().unwrap());
+            for step in &path.steps {
+                let expected = if step.record_value > step.value {
+                    ChildType::First
+                } else {
+                    ChildType::Second
+                };
+                assert_eq!(step.direction, expected);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_missing_values() {
+        let features = [1.0, 2.0, 3.0, f64::NAN, f64::NAN, 8.0, 9.0, 10.0]
+            .into_iter()
+            .map(|value| vec![value, 0.0])
+            .collect::<Vec<_>>();
+        let targets = vec![0, 0, 0, 1, 1, 1, 1, 1];
+        let mut model = ClassificationTreeBuilder::default().build().unwrap();
+        model.fit(&features, &targets).await;
+
+        let root = model.nodes.root().unwrap();
+        assert_eq!(model.nodes.nodes[root.index].data.default_direction, ChildType::First);
+        let preds = model
+            .predict(&[vec![f64::NAN, 0.0], vec![2.0, 0.0], vec![9.0, 0.0]])
+            .await;
+        assert_eq!(preds, vec![1, 0, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_leaf_rule() {
+        // the root's best split separates the classes perfectly, it mustn't be made a leaf
+        let features = (0..4).map(|i| vec![i as f64, 0.0]).collect::<Vec<_>>();
+        let targets = vec![0, 0, 1, 1];
+        let mut model = ClassificationTreeBuilder::default().build().unwrap();
+        model.fit(&features, &targets).await;
+        assert_eq!(model.nodes.nodes.len(), 3);
+        assert_eq!(model.predict(&features).await, targets);
+
+        // identical records of different classes can't be separated, they make an impure leaf
+        let features = vec![vec![1.0, 0.0]; 4];
+        let targets = vec![0, 1, 1, 0];
+        model.fit(&features, &targets).await;
+        assert_eq!(model.nodes.nodes.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_categorical_features() {
+        // grades 0 and 2 are one class and 1 and 3 the other, which no single threshold separates
+        let features = [0.0, 1.0, 2.0, 3.0, 0.0, 1.0, 2.0, 3.0, f64::NAN]
+            .into_iter()
+            .map(|grade| vec![grade, 0.0])
+            .collect::<Vec<_>>();
+        let targets = vec![1, 0, 1, 0, 1, 0, 1, 0, 0];
+        let mut model = ClassificationTreeBuilder::default()
+            .categorical_features(vec![0])
+            .build()
+            .unwrap();
+        model.fit(&features, &targets).await;
+
+        let root = &model.nodes.nodes[model.nodes.root().unwrap().index];
+        assert_eq!(root.data.categories, Some(CategorySet::from_iter([1, 3])));
+        assert_eq!(model.predict(&features).await, targets);
+        // a grade not seen in training is treated like a missing one
+        let unseen = model.predict(&[vec![7.0, 0.0], vec![f64::NAN, 0.0]]).await;
+        assert_eq!(unseen, vec![0, 0]);
+
+        // three classes each spread over two categories, one with a very large code
+        let features = [0.0, 1.0, 2.0, 1e12, 5.0, 6.0]
+            .repeat(2)
+            .into_iter()
+            .map(|grade| vec![grade, 0.0])
+            .collect::<Vec<_>>();
+        let targets = [0, 1, 2, 0, 1, 2].repeat(2);
+        model.fit(&features, &targets).await;
+        assert_eq!(model.predict(&features).await, targets);
+    }
+
+    #[tokio::test]
+    async fn test_sample_weights() {
+        let (features, targets) = load_milk_train_dataset();
+        let mut unweighted = ClassificationTreeBuilder::default().build().unwrap();
+        unweighted.fit(&features, &targets).await;
+        let mut weighted = ClassificationTreeBuilder::default().build().unwrap();
+        weighted
+            .fit_weighted(&features, &targets, &vec![2.0; targets.len()])
+            .await;
+        assert_eq!(
+            unweighted.predict(&features).await,
+            weighted.predict(&features).await
+        );
+
+        // a heavily weighted record outvotes the rest of its leaf
+        let features = vec![vec![1.0, 0.0], vec![1.0, 0.0], vec![1.0, 0.0]];
+        let targets = vec![0, 0, 1];
+        let mut model = ClassificationTreeBuilder::default().build().unwrap();
+        model.fit_weighted(&features, &targets, &[1.0, 1.0, 5.0]).await;
+        assert_eq!(model.predict(&[vec![1.0, 0.0]]).await, vec![1]);
+
+        // no split may leave a child with less than half the total weight
+        let features = (0..10).map(|i| vec![i as f64, 0.0]).collect::<Vec<_>>();
+        let targets = vec![0, 0, 0, 0, 0, 0, 0, 0, 1, 1];
+        let mut model = ClassificationTreeBuilder::default()
+            .min_weight_fraction_leaf(0.5)
+            .build()
+            .unwrap();
+        model.fit_weighted(&features, &targets, &[1.0; 10]).await;
+        for leaf in model.nodes.leaves() {
+            assert!(model.nodes.nodes[leaf.index].data.weighted_n_samples >= 5.0);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shap_values_without_weight() {
+        // the record of zero weight leaves a child no weight reached under a node that had weight
+        let features = vec![vec![0.0, 0.0], vec![1.0, 0.0], vec![1.0, 0.0]];
+        let targets = vec![1, 0, 1];
+        let mut model = ClassificationTreeBuilder::default().build().unwrap();
+        model.fit_weighted(&features, &targets, &[0.0, 1.0, 1.0]).await;
+
+        let shap = model.shap_values(&features, &1);
+        let probabilities = model.predict_proba(&features).await;
+        for (phi, probabilities) in shap.values.iter().zip(&probabilities) {
+            assert!(phi.iter().all(|value| value.is_finite()));
+            let output = shap.expected_value + phi.iter().sum::<f64>();
+            assert!((output - probabilities[1]).abs() < 1e-9);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_class_weight() {
+        // the minority class is a third of the records where the feature is one
+        let mut features = vec![vec![0.0, 0.0]; 91];
+        features.extend(vec![vec![1.0, 0.0]; 9]);
+        let mut targets = vec![0; 90];
+        targets.push(1);
+        targets.extend([0, 0, 0, 0, 0, 0, 1, 1, 1]);
+
+        let mut unweighted = ClassificationTreeBuilder::default().build().unwrap();
+        unweighted.fit(&features, &targets).await;
+        assert_eq!(unweighted.predict(&[vec![1.0, 0.0]]).await, vec![0]);
+
+        let mut balanced = ClassificationTreeBuilder::default()
+            .class_weight(ClassWeight::Balanced)
+            .build()
+            .unwrap();
+        balanced.fit(&features, &targets).await;
+        assert_eq!(balanced.predict(&[vec![1.0, 0.0], vec![0.0, 0.0]]).await, vec![1, 0]);
+
+        let class_weights = ClassWeight::Balanced.weights(&targets);
+        assert!((class_weights[&0] * 96.0 - class_weights[&1] * 4.0).abs() < 1e-9);
+
+        let mut explicit = ClassificationTreeBuilder::default()
+            .class_weight(ClassWeight::Explicit(BTreeMap::from([(1, 100.0)])))
+            .build()
+            .unwrap();
+        explicit.fit(&features, &targets).await;
+        assert_eq!(explicit.predict(&[vec![0.0, 0.0]]).await, vec![1]);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "class weights must be finite and non-negative")]
+    async fn test_negative_class_weight() {
+        let mut model = ClassificationTreeBuilder::default()
+            .class_weight(ClassWeight::Explicit(BTreeMap::from([(1, -1.0)])))
+            .build()
+            .unwrap();
+        model.fit(&vec![vec![0.0], vec![1.0]], &[0, 1]).await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "multiplied by class weights must be finite")]
+    async fn test_class_weight_overflow() {
+        let mut model = ClassificationTreeBuilder::default()
+            .class_weight(ClassWeight::Explicit(BTreeMap::from([(1, 1e10)])))
+            .build()
+            .unwrap();
+        model
+            .fit_weighted(&vec![vec![0.0], vec![1.0]], &[0, 1], &[1.0, 1e300])
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_cost_matrix() {
+        let features = vec![vec![0.0, 0.0]; 100];
+        let mut targets = vec![0; 95];
+        targets.extend([1; 5]);
+        // a missed positive costs fifty times a false alarm
+        let cost_matrix =
+            CostMatrix::new(vec![0, 1], vec![vec![0.0, 1.0], vec![50.0, 0.0]]).unwrap();
+
+        let mut model = ClassificationTreeBuilder::default().build().unwrap();
+        model.fit(&features, &targets).await;
+        assert_eq!(model.predict(&[vec![0.0, 0.0]]).await, vec![0]);
+        assert_eq!(
+            model.predict_min_cost(&[vec![0.0, 0.0]], &cost_matrix).await,
+            Ok(vec![1])
+        );
+        let partial_cost_matrix = CostMatrix::new(vec![0], vec![vec![0.0]]).unwrap();
+        assert_eq!(
+            model.predict_min_cost(&[vec![0.0, 0.0]], &partial_cost_matrix).await,
+            Err(CostMatrixError::MissingClass(1))
+        );
+
+        // costs are applied while fitting, so the class stored in each node reflects them
+        let mut model = ClassificationTreeBuilder::default()
+            .cost_matrix(cost_matrix)
+            .build()
+            .unwrap();
+        model.fit(&features, &targets).await;
+        assert_eq!(model.predict(&[vec![0.0, 0.0]]).await, vec![1]);
+        let root = model.nodes.root().unwrap();
+        assert_eq!(model.nodes.nodes[root.index].data.encoded_prediction, 1);
+    }
+
+    #[tokio::test]
+    async fn test_cost_matrix_splits() {
+        // column 0 isolates class 0 and column 1 isolates class 2
+        let mut features = vec![vec![0.0, 0.0]; 40];
+        features.extend(vec![vec![1.0, 0.0]; 40]);
+        features.extend(vec![vec![1.0, 1.0]; 20]);
+        let mut targets = vec![0; 40];
+        targets.extend([1; 40]);
+        targets.extend([2; 20]);
+
+        let mut model = ClassificationTreeBuilder::default().build().unwrap();
+        model.fit(&features, &targets).await;
+        let root = model.nodes.root().unwrap();
+        assert_eq!(model.nodes.nodes[root.index].data.column, 0);
+
+        // confusing classes 0 and 1 is free, so only isolating class 2 is worth anything
+        let cost_matrix = CostMatrix::new(
+            vec![0, 1, 2],
+            vec![vec![0.0, 0.0, 1.0], vec![0.0, 0.0, 1.0], vec![1.0, 1.0, 0.0]],
+        )
+        .unwrap();
+        let mut model = ClassificationTreeBuilder::default()
+            .cost_matrix(cost_matrix)
+            .build()
+            .unwrap();
+        model.fit(&features, &targets).await;
+        let root = model.nodes.root().unwrap();
+        assert_eq!(model.nodes.nodes[root.index].data.column, 1);
+    }
+
+    #[tokio::test]
+    async fn test_string_labels() {
+        let (features, targets) = load_milk_train_dataset();
+        let grade = |target: &isize| ["low", "medium", "high"][*target as usize].to_string();
+        let labels = targets.iter().map(grade).collect::<Vec<_>>();
+
+        let mut model = ClassificationTreeBuilder::default().build().unwrap();
+        model.fit(&features, &targets).await;
+        let mut labelled_model = ClassificationTreeBuilder::default().build().unwrap();
+        labelled_model.fit(&features, &labels).await;
+
+        let preds = model.predict(&features).await;
+        let labelled_preds = labelled_model.predict(&features).await;
+        assert_eq!(labelled_preds, preds.iter().map(grade).collect::<Vec<_>>());
+        assert_eq!(labelled_model.score(&features, &labels, Metric::Accuracy).await, 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_split_last_column() {
+        // only the last column separates the classes
+        let features = (0..20).map(|i| vec![0.0, (i % 2) as f64]).collect::<Vec<_>>();
+        let targets = (0..20).map(|i| i % 2).collect::<Vec<_>>();
+        let mut model = ClassificationTreeBuilder::default().build().unwrap();
+        model.fit(&features, &targets).await;
+
+        let root = model.nodes.root().unwrap();
+        assert_eq!(model.nodes.nodes[root.index].data.node_type, NodeType::Branch);
+        assert_eq!(model.nodes.nodes[root.index].data.column, 1);
+        assert_eq!(model.predict(&features).await, targets);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "Features and targets are of differing length")]
+    async fn test_fit_length_mismatch() {
+        let (features, targets) = load_milk_train_dataset();
+        let mut model = ClassificationTreeBuilder::default().build().unwrap();
+        model.fit(&features, &targets[1..]).await;
+    }
+
+    #[tokio::test]
+    async fn test_refit() {
+        let (features, targets) = load_milk_train_dataset();
+        let mut model = ClassificationTreeBuilder::default().build().unwrap();
+        model.fit(&features, &targets).await;
+
+        // refitting replaces the tree rather than growing a second one beside it
+        model.fit(&features, &vec![1; targets.len()]).await;
+        assert_eq!(model.nodes.nodes.len(), 1);
+        assert_eq!(model.predict(&features).await, vec![1; features.len()]);
+    }
+
+    #[tokio::test]
+    async fn test_dataset() {
+        let (features, targets) = load_milk_train_dataset();
+        let names = ["ph", "temperature", "taste", "odor", "fat", "turbidity", "colour"];
+        let dataset =
+            Dataset::from_rows(&features).with_feature_names(names.map(String::from).to_vec());
+
+        let mut model = ClassificationTreeBuilder::default().build().unwrap();
+        model.fit(&features, &targets).await;
+        let mut dataset_model = ClassificationTreeBuilder::default().build().unwrap();
+        dataset_model.fit(&dataset, &targets).await;
+        assert_eq!(
+            model.predict(&features).await,
+            dataset_model.predict(&dataset).await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sparse_features() {
+        let (mut features, targets) = load_milk_train_dataset();
+        // negative values and missing values either side of the implicit zeros
+        for (i, record) in features.iter_mut().enumerate() {
+            record[2] -= 0.5;
+            if i % 7 == 0 {
+                record[3] = f64::NAN;
+            }
+        }
+        let (test_features, _) = load_milk_test_dataset();
+        let csc = CscMatrix::from_dense(&features);
+        let csr = CsrMatrix::from_dense(&test_features);
+
+        let mut model = ClassificationTreeBuilder::default().build().unwrap();
+        model.fit(&features, &targets).await;
+        let mut sparse_model = ClassificationTreeBuilder::default().build().unwrap();
+        sparse_model.fit(&csc, &targets).await;
+
+        let splits = |model: &ClassificationTree| {
+            model
+                .nodes
+                .nodes
+                .iter()
+                .map(|node| match node.data.node_type {
+                    NodeType::Branch => Some((
+                        node.data.column,
+                        node.data.value.to_bits(),
+                        node.data.categories.clone(),
+                    )),
+                    NodeType::Leaf => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(splits(&model), splits(&sparse_model));
+        assert_eq!(
+            sparse_model.predict(&csr).await,
+            model.predict(&test_features).await
+        );
+
+        // categorical columns with implicit zeros and missing values
+        let categorical = || {
+            ClassificationTreeBuilder::default()
+                .categorical_features(vec![3, 4])
+                .max_depth(4)
+                .build()
+                .unwrap()
+        };
+        let mut model = categorical();
+        model.fit(&features, &targets).await;
+        let mut sparse_model = categorical();
+        sparse_model.fit(&csc, &targets).await;
+        assert_eq!(splits(&model), splits(&sparse_model));
+        assert_eq!(
+            sparse_model.predict(&csr).await,
+            model.predict(&test_features).await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sparse_missing_values() {
+        // whether a value is missing is the only signal, with the present values either non-zero
+        // or implicit zeros
+        for present in [1.0, 0.0] {
+            let features = (0..10)
+                .map(|i| vec![if i < 5 { f64::NAN } else { present }, 0.0])
+                .collect::<Vec<_>>();
+            let targets = (0..10).map(|i| if i < 5 { 0 } else { 1 }).collect::<Vec<isize>>();
+            let csc = CscMatrix::from_dense(&features);
+
+            let mut model = ClassificationTreeBuilder::default().build().unwrap();
+            model.fit(&features, &targets).await;
+            let mut sparse_model = ClassificationTreeBuilder::default().build().unwrap();
+            sparse_model.fit(&csc, &targets).await;
+
+            assert_eq!(model.nodes.nodes.len(), 3);
+            assert_eq!(sparse_model.nodes.nodes.len(), 3);
+            assert_eq!(model.predict(&features).await, targets);
+            assert_eq!(sparse_model.predict(&CsrMatrix::from_dense(&features)).await, targets);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_classification_metrics() {
+        let (features, targets) = load_milk_train_dataset();
+        let (test_features, test_targets) = load_milk_test_dataset();
+        let mut model = ClassificationTreeBuilder::default().build().unwrap();
+        model.fit(&features, &targets).await;
+        let predictions = model.predict(&test_features).await;
+
+        let accuracy = model.score(&test_features, &test_targets, Metric::Accuracy).await;
+        let micro_f1 = model
+            .score(&test_features, &test_targets, Metric::F1Score(Average::Micro))
+            .await;
+        assert!((accuracy - micro_f1).abs() < 1e-12);
+        for average in [Average::Binary(2), Average::Macro, Average::Weighted] {
+            assert_eq!(
+                model
+                    .score(&test_features, &test_targets, Metric::Recall(average))
+                    .await,
+                recall(&predictions, &test_targets, &average)
+            );
+        }
+        let labels = ["low", "medium", "high"];
+        let named_targets = test_targets.iter().map(|t| labels[*t as usize]).collect::<Vec<_>>();
+        let named_predictions = predictions.iter().map(|p| labels[*p as usize]).collect::<Vec<_>>();
+        assert_eq!(
+            precision(&named_predictions, &named_targets, &Average::Binary("high")),
+            precision(&predictions, &test_targets, &Average::Binary(2))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_predict_proba() {
+        let (features, targets) = load_milk_train_dataset();
+        let (test_features, test_targets) = load_milk_test_dataset();
+        let mut model = ClassificationTreeBuilder::default()
+            .min_weight_fraction_leaf(0.05)
+            .build()
+            .unwrap();
+        model.fit(&features, &targets).await;
+        assert_eq!(model.classes(), [0, 1, 2]);
+
+        let probabilities = model.predict_proba(&test_features).await;
+        let predictions = model.predict(&test_features).await;
+        for (row, prediction) in probabilities.iter().zip(&predictions) {
+            assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+            let max = row.iter().copied().fold(0.0, f64::max);
+            assert_eq!(row[*prediction as usize], max);
+        }
+
+        let auc = model
+            .score(&test_features, &test_targets, Metric::RocAuc(Average::Macro))
+            .await;
+        assert!(auc > 0.9 && auc <= 1.0);
+        let log_loss = model.score(&test_features, &test_targets, Metric::NegLogLoss).await;
+        assert_eq!(log_loss, -metrics::log_loss(&probabilities, &test_targets, &[0, 1, 2]));
+        let brier = model.score(&test_features, &test_targets, Metric::NegBrierScore).await;
+        assert!(brier < 0.0 && brier > -2.0);
+
+        // leaves only records of zero weight reached fall back to a uniform distribution
+        let record = vec![test_features[0].clone()];
+        let leaf = model.apply(&record).await[0];
+        for count in model.nodes.nodes[leaf.index].data.encoded_class_counts.values_mut() {
+            *count = 0.0;
+        }
+        let probabilities = model.predict_proba(&record).await;
+        assert_eq!(probabilities[0], [1.0 / 3.0; 3]);
+    }
+
+    #[tokio::test]
+    async fn test_custom_scorer() {
+        /// Profit of acting on predictions of high grade milk
+        struct ExpectedProfit;
+
+        #[async_trait]
+        impl Scorer for ExpectedProfit {
+            async fn score(
+                &self,
+                model: &(dyn Model + Sync),
+                features: &dyn Features,
+                targets: &[isize],
+            ) -> f64 {
+                let probabilities = model.predict_proba(features).await;
+                probabilities
+                    .iter()
+                    .zip(targets)
+                    .map(|(row, target)| match (row[2] > 0.5, *target == 2) {
+                        (true, true) => 10.0,
+                        (true, false) => -5.0,
+                        (false, _) => 0.0,
+                    })
+                    .sum()
+            }
+
+            // only acting on a strictly greater profit is worth the effort
+            fn at_least_as_good(&self, score: f64, other: f64) -> bool {
+                score > other
+            }
+        }
+
+        let (features, targets) = load_milk_train_dataset();
+        let (test_features, test_targets) = load_milk_test_dataset();
+        let mut model = ClassificationTreeBuilder::default().build().unwrap();
+        model.fit(&features, &targets).await;
+
+        let profit = model.score(&test_features, &test_targets, ExpectedProfit).await;
+        let scorers: [&dyn Scorer; 2] = [&ExpectedProfit, &Metric::Accuracy];
+        assert_eq!(model.score(&test_features, &test_targets, scorers[0]).await, profit);
+        assert!(profit > 0.0);
+        assert!(!<&ExpectedProfit as Scorer>::at_least_as_good(&&ExpectedProfit, 1.0, 1.0));
+
+        // an error rate is a loss so pruning must not let it grow
+        let error_rate = make_scorer(
+            |predictions: &[isize], targets: &[isize]| {
+                1.0 - metrics::accuracy(predictions, targets)
+            },
+            false,
+        );
+        let accuracy = model.score(&test_features, &test_targets, Metric::Accuracy).await;
+        assert_eq!(
+            model.score(&test_features, &test_targets, &error_rate).await,
+            1.0 - accuracy
+        );
+        let report = model
+            .prune_with_validation_by(&test_features, &test_targets, &error_rate)
+            .await;
+        assert!(report.score_after <= report.score_before);
+    }
+
+    #[tokio::test]
+    async fn test_cross_validation() {
+        let (features, targets) = load_milk_train_dataset();
+        let scores = cross_val_score(
+            || ClassificationTreeBuilder::default().build().unwrap(),
+            &features,
+            &targets,
+            &StratifiedKFold::new(5).with_shuffle(42),
+            Metric::Accuracy,
+        )
+        .await;
+        assert_eq!(scores.len(), 5);
+        assert!(scores.iter().all(|score| *score > 0.8 && *score <= 1.0));
     }
 }
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::hash::Hash;
 
 
@@ -33,13 +33,35 @@ where
         *count.entry(value).or_insert(0) += 1;
     }
     let sum: f64 = count
-        .into_iter()
-        .map(|(_, c)| c)
+        .into_values()
         .map(|x| p_squared(x, len))
         .sum();
     1.0f64 - sum
 }
 
+/// Gini impurity of a node given the total weight of the records of each class in it
+///
+/// # Arguments
+/// * `class_weights` - the total weight of the records of each class, the number of records when
+///   unweighted
+///
+/// # Returns
+/// the gini impurity, zero for a node without any weight
+pub fn gini_impurity_from_weights<K>(class_weights: &BTreeMap<K, f64>) -> f64 {
+    let len = class_weights.values().sum::<f64>();
+    if len == 0.0 {
+        return 0.0;
+    }
+    let sum: f64 = class_weights
+        .values()
+        .map(|count| {
+            let p = *count / len;
+            p * p
+        })
+        .sum();
+    1.0f64 - sum
+}
+
 /// Calculate the weighted average of the gini impurity of two nodes for a given split
 ///
 /// # Arguments
@@ -48,12 +70,63 @@ where
 ///
 /// # Returns
 /// the weighted gini impurity
+#[deprecated(note = "use `weighted_gini_impurity_from_weights`, which also takes weighted counts")]
 pub fn weighted_gini_impurity(node_1_targets: Vec<&isize>, node_2_targets: Vec<&isize>) -> f64 {
-    let node_1_len = node_1_targets.len();
-    let node_2_len = node_2_targets.len();
+    let class_weights = |targets: Vec<&isize>| {
+        let mut class_weights = BTreeMap::new();
+        for target in targets {
+            *class_weights.entry(*target).or_insert(0.0) += 1.0;
+        }
+        class_weights
+    };
+    weighted_gini_impurity_from_weights(
+        &class_weights(node_1_targets),
+        &class_weights(node_2_targets),
+    )
+}
+
+/// Calculate the weighted average of the gini impurity of two nodes for a given split
+///
+/// # Arguments
+/// * `node_1_weights` - the total weight of the records of each class in node 1
+/// * `node_2_weights` - the total weight of the records of each class in node 2
+///
+/// # Returns
+/// the weighted gini impurity, zero if neither node has any weight
+pub fn weighted_gini_impurity_from_weights<K>(
+    node_1_weights: &BTreeMap<K, f64>,
+    node_2_weights: &BTreeMap<K, f64>,
+) -> f64 {
+    let node_1_len = node_1_weights.values().sum::<f64>();
+    let node_2_len = node_2_weights.values().sum::<f64>();
+    if node_1_len + node_2_len == 0.0 {
+        return 0.0;
+    }
 
-    let node_1_weight = node_1_len as f64 / (node_1_len + node_2_len) as f64;
-    let node_2_weight = node_2_len as f64 / (node_1_len + node_2_len) as f64;
+    let node_1_weight = node_1_len / (node_1_len + node_2_len);
+    let node_2_weight = node_2_len / (node_1_len + node_2_len);
 
-    node_1_weight * gini_impurity(node_1_targets) + node_2_weight * gini_impurity(node_2_targets)
+    node_1_weight * gini_impurity_from_weights(node_1_weights)
+        + node_2_weight * gini_impurity_from_weights(node_2_weights)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gini_impurity() {
+        let node_1 = BTreeMap::from([(0, 3.0), (1, 1.0)]);
+        let node_2 = BTreeMap::from([(1, 2.0)]);
+        assert_eq!(gini_impurity_from_weights(&node_1), 0.375);
+        assert_eq!(weighted_gini_impurity_from_weights(&node_1, &node_2), 0.25);
+        #[allow(deprecated)]
+        let unweighted = weighted_gini_impurity(vec![&0, &0, &0, &1], vec![&1, &1]);
+        assert_eq!(unweighted, weighted_gini_impurity_from_weights(&node_1, &node_2));
+
+        // records of zero weight carry no impurity
+        let weightless = BTreeMap::from([(0, 0.0), (1, 0.0)]);
+        assert_eq!(gini_impurity_from_weights(&weightless), 0.0);
+        assert_eq!(weighted_gini_impurity_from_weights(&weightless, &weightless), 0.0);
+    }
 }
@@ -0,0 +1,193 @@
+use crate::metrics::{accuracy, class_counts};
+use std::fmt::{Display, Formatter};
+
+/// Scores of the predictions of one class, or an average over the classes
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClassScores {
+    pub precision: f64,
+    pub recall: f64,
+    pub f1_score: f64,
+    /// the number of targets the scores cover
+    pub support: usize,
+}
+
+/// Precision, recall and F1 score of each class along with their averages
+///
+/// # Arguments
+/// * `classes` - the scores of every label appearing in the targets or predictions, sorted
+/// * `accuracy` - the proportion of correct predictions
+/// * `macro_average` - the unweighted mean of the scores of each class
+/// * `weighted_average` - the mean of the scores of each class weighted by its support
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClassificationReport<L = isize> {
+    pub classes: Vec<(L, ClassScores)>,
+    pub accuracy: f64,
+    pub macro_average: ClassScores,
+    pub weighted_average: ClassScores,
+}
+
+/// Score the predictions of each class
+///
+/// # Arguments
+/// * `predictions` - predicted labels
+/// * `targets` - true labels
+///
+/// # Returns
+/// the report, which displays as a text table
+pub fn classification_report<L: Ord + Clone>(
+    predictions: &[L],
+    targets: &[L],
+) -> ClassificationReport<L> {
+    let classes = class_counts(predictions, targets)
+        .into_iter()
+        .map(|(label, counts)| {
+            let scores = ClassScores {
+                precision: counts.precision(),
+                recall: counts.recall(),
+                f1_score: counts.f1_score(),
+                support: counts.support(),
+            };
+            (label, scores)
+        })
+        .collect::<Vec<_>>();
+
+    let n_targets = targets.len();
+    let average = |weight: &dyn Fn(&ClassScores) -> f64| {
+        let total_weight = classes
+            .iter()
+            .map(|(_, scores)| weight(scores))
+            .sum::<f64>();
+        let mean = |score: fn(&ClassScores) -> f64| match total_weight {
+            0.0 => 0.0,
+            _ => {
+                classes
+                    .iter()
+                    .map(|(_, scores)| score(scores) * weight(scores))
+                    .sum::<f64>()
+                    / total_weight
+            }
+        };
+        ClassScores {
+            precision: mean(|scores| scores.precision),
+            recall: mean(|scores| scores.recall),
+            f1_score: mean(|scores| scores.f1_score),
+            support: n_targets,
+        }
+    };
+    let macro_average = average(&|_| 1.0);
+    let weighted_average = average(&|scores| scores.support as f64);
+
+    ClassificationReport {
+        accuracy: match n_targets {
+            0 => 0.0,
+            _ => accuracy(predictions, targets),
+        },
+        classes,
+        macro_average,
+        weighted_average,
+    }
+}
+
+impl<L: Display> Display for ClassificationReport<L> {
+    /// A table of the scores of each class followed by accuracy and the averages
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let labels = self
+            .classes
+            .iter()
+            .map(|(label, _)| label.to_string())
+            .collect::<Vec<_>>();
+        let width = labels
+            .iter()
+            .map(|label| label.len())
+            .chain(["weighted avg".len()])
+            .max()
+            .unwrap_or_default();
+        let scores_row = |f: &mut Formatter<'_>, name: &str, scores: &ClassScores| {
+            writeln!(
+                f,
+                "{name:>width$} {:>9.2} {:>9.2} {:>9.2} {:>9}",
+                scores.precision, scores.recall, scores.f1_score, scores.support
+            )
+        };
+
+        writeln!(
+            f,
+            "{:>width$} {:>9} {:>9} {:>9} {:>9}",
+            "", "precision", "recall", "f1-score", "support"
+        )?;
+        writeln!(f)?;
+        for (label, (_, scores)) in labels.iter().zip(&self.classes) {
+            scores_row(f, label, scores)?;
+        }
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:>width$} {:>9} {:>9} {:>9.2} {:>9}",
+            "accuracy", "", "", self.accuracy, self.macro_average.support
+        )?;
+        scores_row(f, "macro avg", &self.macro_average)?;
+        scores_row(f, "weighted avg", &self.weighted_average)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::confusion_matrix;
+
+    #[test]
+    fn test_classification_report() {
+        let targets = ["cat", "dog", "cat", "bird", "dog", "dog"];
+        let predictions = ["cat", "cat", "cat", "bird", "dog", "bird"];
+
+        let matrix = confusion_matrix(&predictions, &targets);
+        assert_eq!(matrix.labels(), ["bird", "cat", "dog"]);
+        assert_eq!(
+            matrix.counts(),
+            [vec![1, 0, 0], vec![0, 2, 0], vec![1, 1, 1]]
+        );
+        assert_eq!(matrix.count(&"dog", &"cat"), 1);
+        assert_eq!(matrix.count(&"fish", &"cat"), 0);
+        assert_eq!(
+            matrix.to_string(),
+            "target \\ predicted bird  cat  dog
+              bird    1    0    0
+               cat    0    2    0
+               dog    1    1    1
+"
+        );
+
+        let report = classification_report(&predictions, &targets);
+        assert_eq!(report.classes[1].0, "cat");
+        assert_eq!(report.classes[1].1.precision, 2.0 / 3.0);
+        assert_eq!(report.classes[2].1.recall, 1.0 / 3.0);
+        assert_eq!(report.classes[2].1.support, 3);
+        assert_eq!(report.accuracy, 4.0 / 6.0);
+        assert_eq!(report.macro_average.recall, (1.0 + 1.0 + 1.0 / 3.0) / 3.0);
+        assert_eq!(report.weighted_average.recall, report.accuracy);
+        assert_eq!(
+            report.to_string(),
+            "             precision    recall  f1-score   support
+
+        bird      0.50      1.00      0.67         1
+         cat      0.67      1.00      0.80         2
+         dog      1.00      0.33      0.50         3
+
+    accuracy                          0.67         6
+   macro avg      0.72      0.78      0.66         6
+weighted avg      0.81      0.67      0.63         6
+"
+        );
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&report).unwrap();
+            let deserialized: ClassificationReport<String> = serde_json::from_str(&json).unwrap();
+            assert_eq!(deserialized.classes[0].0, "bird");
+            let json = serde_json::to_string(&matrix).unwrap();
+            assert!(json.contains(r#""counts":[[1,0,0],[0,2,0],[1,1,1]]"#));
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+/// Counts of each combination of target and predicted label
+///
+/// # Arguments
+/// * `labels` - every label appearing in the targets or predictions, sorted
+/// * `counts` - `counts[i][j]` is the number of records with target `labels[i]` that were
+///   predicted as `labels[j]`
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConfusionMatrix<L = isize> {
+    labels: Vec<L>,
+    counts: Vec<Vec<usize>>,
+}
+
+impl<L: Ord> ConfusionMatrix<L> {
+    pub fn labels(&self) -> &[L] {
+        &self.labels
+    }
+
+    /// The counts, one row per target label and one column per predicted label
+    pub fn counts(&self) -> &[Vec<usize>] {
+        &self.counts
+    }
+
+    /// The number of records with a target that were predicted as `predicted`, 0 for unseen
+    /// labels
+    pub fn count(&self, target: &L, predicted: &L) -> usize {
+        match (
+            self.labels.binary_search(target),
+            self.labels.binary_search(predicted),
+        ) {
+            (Ok(target), Ok(predicted)) => self.counts[target][predicted],
+            _ => 0,
+        }
+    }
+}
+
+/// Count each combination of target and predicted label
+///
+/// # Arguments
+/// * `predictions` - predicted labels
+/// * `targets` - true labels
+///
+/// # Returns
+/// the confusion matrix, with targets as rows and predictions as columns
+pub fn confusion_matrix<L: Ord + Clone>(predictions: &[L], targets: &[L]) -> ConfusionMatrix<L> {
+    assert!(
+        predictions.len() == targets.len(),
+        "Predictions and targets are of differing length, cannot calculate confusion matrix"
+    );
+    let labels = predictions
+        .iter()
+        .chain(targets)
+        .cloned()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    let mut counts = vec![vec![0; labels.len()]; labels.len()];
+    let index = |label: &L| {
+        labels
+            .binary_search(label)
+            .expect("Every label was collected")
+    };
+    for (prediction, target) in predictions.iter().zip(targets) {
+        counts[index(target)][index(prediction)] += 1;
+    }
+    ConfusionMatrix { labels, counts }
+}
+
+impl<L: Display> Display for ConfusionMatrix<L> {
+    /// A table with a row per target label and a column per predicted label
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let labels = self
+            .labels
+            .iter()
+            .map(|label| label.to_string())
+            .collect::<Vec<_>>();
+        let corner = "target \\ predicted";
+        let label_width = labels
+            .iter()
+            .map(|label| label.len())
+            .chain([corner.len()])
+            .max()
+            .unwrap_or_default();
+        let count_width = labels
+            .iter()
+            .map(|label| label.len())
+            .chain(self.counts.iter().flatten().map(|count| count.to_string().len()))
+            .max()
+            .unwrap_or_default();
+
+        write!(f, "{corner:>label_width$}")?;
+        for label in &labels {
+            write!(f, " {label:>count_width$}")?;
+        }
+        writeln!(f)?;
+        for (label, row) in labels.iter().zip(&self.counts) {
+            write!(f, "{label:>label_width$}")?;
+            for count in row {
+                write!(f, " {count:>count_width$}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
@@ -1,9 +1,34 @@
 mod accuracy;
+mod classification_report;
+mod confusion_matrix;
+mod precision_recall;
+mod probabilistic;
+mod ranking;
+mod regression;
+mod scorer;
 pub use accuracy::*;
+pub use classification_report::*;
+pub use confusion_matrix::*;
+pub use precision_recall::*;
+pub use probabilistic::*;
+pub use ranking::*;
+pub use regression::*;
+pub use scorer::*;
 
-pub enum Metric{
+/// A metric to score a model's predictions with, greater is better
+///
+/// `F1Score`, `Precision` and `Recall` combine the scores of each class as set by their
+/// [`Average`], as do `RocAuc` and `AveragePrecision` which along with `NegLogLoss` and
+/// `NegBrierScore` score predicted probabilities. Log loss and Brier score are negated so that
+/// greater is better.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Metric<L = isize> {
     Accuracy,
-    F1Score,
-    Precision,
-    Recall
-}
\ No newline at end of file
+    F1Score(Average<L>),
+    Precision(Average<L>),
+    Recall(Average<L>),
+    RocAuc(Average<L>),
+    AveragePrecision(Average<L>),
+    NegLogLoss,
+    NegBrierScore,
+}
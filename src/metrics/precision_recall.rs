@@ -0,0 +1,212 @@
+use std::collections::BTreeMap;
+
+/// How per-class scores are combined into a single score
+///
+/// * `Binary` - only score the given positive label
+/// * `Micro` - pool the true positives, false positives and false negatives of every class
+/// * `Macro` - the unweighted mean of the score of each class
+/// * `Weighted` - the mean of the score of each class weighted by its number of targets
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Average<L = isize> {
+    Binary(L),
+    Micro,
+    Macro,
+    Weighted,
+}
+
+/// Counts of the outcomes of predicting one class
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct ClassCounts {
+    pub(crate) true_positives: usize,
+    pub(crate) false_positives: usize,
+    pub(crate) false_negatives: usize,
+}
+
+impl ClassCounts {
+    pub(crate) fn precision(&self) -> f64 {
+        ratio(
+            self.true_positives,
+            self.true_positives + self.false_positives,
+        )
+    }
+
+    pub(crate) fn recall(&self) -> f64 {
+        ratio(
+            self.true_positives,
+            self.true_positives + self.false_negatives,
+        )
+    }
+
+    pub(crate) fn f1_score(&self) -> f64 {
+        ratio(
+            2 * self.true_positives,
+            2 * self.true_positives + self.false_positives + self.false_negatives,
+        )
+    }
+
+    /// The number of targets of the class
+    pub(crate) fn support(&self) -> usize {
+        self.true_positives + self.false_negatives
+    }
+}
+
+/// A ratio of counts, 0 when the denominator is 0
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    match denominator {
+        0 => 0.0,
+        _ => numerator as f64 / denominator as f64,
+    }
+}
+
+/// The outcome counts of every class appearing in either the predictions or the targets
+pub(crate) fn class_counts<L: Ord + Clone>(
+    predictions: &[L],
+    targets: &[L],
+) -> BTreeMap<L, ClassCounts> {
+    assert!(
+        predictions.len() == targets.len(),
+        "Predictions and targets are of differing length, cannot calculate metric"
+    );
+    let mut counts = BTreeMap::<L, ClassCounts>::new();
+    for (prediction, target) in predictions.iter().zip(targets) {
+        if prediction == target {
+            counts.entry(target.clone()).or_default().true_positives += 1;
+        } else {
+            counts
+                .entry(prediction.clone())
+                .or_default()
+                .false_positives += 1;
+            counts.entry(target.clone()).or_default().false_negatives += 1;
+        }
+    }
+    counts
+}
+
+/// Combine the per-class scores of a set of predictions
+fn averaged_score<L: Ord + Clone>(
+    predictions: &[L],
+    targets: &[L],
+    average: &Average<L>,
+    score: fn(&ClassCounts) -> f64,
+) -> f64 {
+    let counts = class_counts(predictions, targets);
+    match average {
+        Average::Binary(positive_label) => {
+            counts.get(positive_label).map(score).unwrap_or_default()
+        }
+        Average::Micro => {
+            let pooled = counts
+                .values()
+                .fold(ClassCounts::default(), |pooled, class| ClassCounts {
+                    true_positives: pooled.true_positives + class.true_positives,
+                    false_positives: pooled.false_positives + class.false_positives,
+                    false_negatives: pooled.false_negatives + class.false_negatives,
+                });
+            score(&pooled)
+        }
+        Average::Macro => match counts.len() {
+            0 => 0.0,
+            n_classes => counts.values().map(score).sum::<f64>() / n_classes as f64,
+        },
+        Average::Weighted => match targets.len() {
+            0 => 0.0,
+            n_targets => {
+                counts
+                    .values()
+                    .map(|class| score(class) * class.support() as f64)
+                    .sum::<f64>()
+                    / n_targets as f64
+            }
+        },
+    }
+}
+
+/// The proportion of predictions of a class that are correct, `tp / (tp + fp)`
+///
+/// # Arguments
+/// * `predictions` - predicted labels
+/// * `targets` - true labels
+/// * `average` - how the precision of each class is combined
+///
+/// # Returns
+/// the precision, classes that are never predicted have a precision of 0
+pub fn precision<L: Ord + Clone>(predictions: &[L], targets: &[L], average: &Average<L>) -> f64 {
+    averaged_score(predictions, targets, average, ClassCounts::precision)
+}
+
+/// The proportion of targets of a class that are predicted, `tp / (tp + fn)`
+///
+/// # Arguments
+/// * `predictions` - predicted labels
+/// * `targets` - true labels
+/// * `average` - how the recall of each class is combined
+///
+/// # Returns
+/// the recall, classes without any targets have a recall of 0
+pub fn recall<L: Ord + Clone>(predictions: &[L], targets: &[L], average: &Average<L>) -> f64 {
+    averaged_score(predictions, targets, average, ClassCounts::recall)
+}
+
+/// The harmonic mean of precision and recall, `2tp / (2tp + fp + fn)`
+///
+/// # Arguments
+/// * `predictions` - predicted labels
+/// * `targets` - true labels
+/// * `average` - how the F1 score of each class is combined
+///
+/// # Returns
+/// the F1 score, classes that are neither predicted nor targets have a score of 0
+pub fn f1_score<L: Ord + Clone>(predictions: &[L], targets: &[L], average: &Average<L>) -> f64 {
+    averaged_score(predictions, targets, average, ClassCounts::f1_score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_averaging() {
+        let targets = [0, 1, 2, 0, 1, 2, 2];
+        let predictions = [0, 2, 1, 0, 0, 2, 2];
+        // class 0: tp 2 fp 1 fn 0, class 1: tp 0 fp 1 fn 2, class 2: tp 2 fp 1 fn 1
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-12;
+
+        assert!(close(
+            precision(&predictions, &targets, &Average::Binary(0)),
+            2.0 / 3.0
+        ));
+        assert!(close(
+            recall(&predictions, &targets, &Average::Binary(2)),
+            2.0 / 3.0
+        ));
+        assert!(close(
+            f1_score(&predictions, &targets, &Average::Binary(1)),
+            0.0
+        ));
+        assert!(close(
+            precision(&predictions, &targets, &Average::Binary(5)),
+            0.0
+        ));
+
+        assert!(close(
+            precision(&predictions, &targets, &Average::Micro),
+            4.0 / 7.0
+        ));
+        assert!(close(
+            recall(&predictions, &targets, &Average::Micro),
+            4.0 / 7.0
+        ));
+        assert!(close(
+            precision(&predictions, &targets, &Average::Macro),
+            (2.0 / 3.0 + 0.0 + 2.0 / 3.0) / 3.0
+        ));
+        assert!(close(
+            recall(&predictions, &targets, &Average::Weighted),
+            (2.0 * 1.0 + 2.0 * 0.0 + 3.0 * 2.0 / 3.0) / 7.0
+        ));
+        assert!(close(
+            f1_score(&predictions, &targets, &Average::Macro),
+            (0.8 + 0.0 + 2.0 / 3.0) / 3.0
+        ));
+    }
+}
@@ -0,0 +1,97 @@
+/// Probabilities are clipped to `[EPSILON, 1 - EPSILON]` before taking logs
+const EPSILON: f64 = 1e-15;
+
+/// The column of each target's class
+fn target_columns<L: PartialEq>(
+    probabilities: &[Vec<f64>],
+    targets: &[L],
+    classes: &[L],
+) -> Vec<usize> {
+    assert!(
+        probabilities.len() == targets.len(),
+        "Probabilities and targets are of differing length, cannot calculate metric"
+    );
+    assert!(
+        probabilities.iter().all(|row| row.len() == classes.len()),
+        "Expected a probability for each of the {} classes",
+        classes.len()
+    );
+    targets
+        .iter()
+        .map(|target| {
+            classes
+                .iter()
+                .position(|class| class == target)
+                .expect("A target isn't one of the classes")
+        })
+        .collect()
+}
+
+/// Cross-entropy between the targets and the predicted probabilities, lower is better
+///
+/// # Arguments
+/// * `probabilities` - the probability of each class for each record, as from
+///   [`crate::Model::predict_proba`]
+/// * `targets` - true labels
+/// * `classes` - the class of each column of `probabilities`
+///
+/// # Returns
+/// the mean negative log probability of each record's target
+pub fn log_loss<L: PartialEq>(probabilities: &[Vec<f64>], targets: &[L], classes: &[L]) -> f64 {
+    let columns = target_columns(probabilities, targets, classes);
+    let total = probabilities
+        .iter()
+        .zip(columns)
+        .map(|(row, column)| -row[column].clamp(EPSILON, 1.0 - EPSILON).ln())
+        .sum::<f64>();
+    total / targets.len() as f64
+}
+
+/// Mean squared error of the predicted probabilities, lower is better
+///
+/// With two classes this is the squared error of the probability of the second, positive, class.
+/// With more it's the squared error summed over every class.
+///
+/// # Arguments
+/// * `probabilities` - the probability of each class for each record, as from
+///   [`crate::Model::predict_proba`]
+/// * `targets` - true labels
+/// * `classes` - the class of each column of `probabilities`
+pub fn brier_score<L: PartialEq>(probabilities: &[Vec<f64>], targets: &[L], classes: &[L]) -> f64 {
+    let columns = target_columns(probabilities, targets, classes);
+    let squared_error = |row: &Vec<f64>, target_column: usize, column: usize| {
+        let outcome = f64::from(u8::from(column == target_column));
+        (row[column] - outcome).powi(2)
+    };
+    let total = probabilities
+        .iter()
+        .zip(columns)
+        .map(|(row, target_column)| match classes.len() {
+            2 => squared_error(row, target_column, 1),
+            _ => (0..row.len())
+                .map(|column| squared_error(row, target_column, column))
+                .sum(),
+        })
+        .sum::<f64>();
+    total / targets.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probabilistic_metrics() {
+        let classes = ["no", "yes"];
+        let targets = ["no", "yes", "yes"];
+        let probabilities = [vec![0.8, 0.2], vec![0.4, 0.6], vec![0.0, 1.0]];
+        let expected = -(0.8f64.ln() + 0.6f64.ln() + (1.0 - EPSILON).ln()) / 3.0;
+        assert!((log_loss(&probabilities, &targets, &classes) - expected).abs() < 1e-12);
+        let expected = (0.04 + 0.16 + 0.0) / 3.0;
+        assert!((brier_score(&probabilities, &targets, &classes) - expected).abs() < 1e-12);
+
+        let classes = [0, 1, 2];
+        let probabilities = [vec![0.5, 0.5, 0.0]];
+        assert!((brier_score(&probabilities, &[1], &classes) - 0.5).abs() < 1e-12);
+    }
+}
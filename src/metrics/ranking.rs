@@ -0,0 +1,291 @@
+use crate::metrics::Average;
+
+/// Points of a receiver operating characteristic curve, in order of decreasing threshold
+///
+/// # Arguments
+/// * `false_positive_rates` - the proportion of negatives scored at or above each threshold
+/// * `true_positive_rates` - the proportion of positives scored at or above each threshold
+/// * `thresholds` - the distinct scores, starting at infinity so the curve starts at `(0, 0)`
+#[derive(Clone, Debug, PartialEq)]
+pub struct RocCurve {
+    pub false_positive_rates: Vec<f64>,
+    pub true_positive_rates: Vec<f64>,
+    pub thresholds: Vec<f64>,
+}
+
+/// Points of a precision-recall curve, in order of decreasing threshold
+///
+/// # Arguments
+/// * `precisions` - the precision of predicting positive at or above each threshold
+/// * `recalls` - the recall of predicting positive at or above each threshold
+/// * `thresholds` - the distinct scores, starting at infinity where precision is taken to be 1
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrecisionRecallCurve {
+    pub precisions: Vec<f64>,
+    pub recalls: Vec<f64>,
+    pub thresholds: Vec<f64>,
+}
+
+/// Cumulative true and false positives at each distinct score, in order of decreasing score
+fn cumulative_positives(scores: &[f64], positives: &[bool]) -> Vec<(f64, usize, usize)> {
+    assert!(
+        scores.len() == positives.len(),
+        "Scores and targets are of differing length, cannot calculate metric"
+    );
+    let mut ordered = scores
+        .iter()
+        .copied()
+        .zip(positives.iter().copied())
+        .collect::<Vec<_>>();
+    ordered.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut points = vec![(f64::INFINITY, 0, 0)];
+    let (mut true_positives, mut false_positives) = (0, 0);
+    for (i, (score, positive)) in ordered.iter().enumerate() {
+        match positive {
+            true => true_positives += 1,
+            false => false_positives += 1,
+        }
+        // records with tied scores are all on the same side of any threshold
+        if ordered.get(i + 1).is_none_or(|(next, _)| next != score) {
+            points.push((*score, true_positives, false_positives));
+        }
+    }
+    points
+}
+
+fn binary_roc_curve(scores: &[f64], positives: &[bool]) -> RocCurve {
+    let points = cumulative_positives(scores, positives);
+    let (_, n_positives, n_negatives) = *points.last().expect("The curve has a starting point");
+    assert!(
+        n_positives > 0 && n_negatives > 0,
+        "Both positive and negative targets are needed, cannot calculate ROC curve"
+    );
+    RocCurve {
+        false_positive_rates: points
+            .iter()
+            .map(|(_, _, fp)| *fp as f64 / n_negatives as f64)
+            .collect(),
+        true_positive_rates: points
+            .iter()
+            .map(|(_, tp, _)| *tp as f64 / n_positives as f64)
+            .collect(),
+        thresholds: points.iter().map(|(threshold, _, _)| *threshold).collect(),
+    }
+}
+
+fn binary_precision_recall_curve(scores: &[f64], positives: &[bool]) -> PrecisionRecallCurve {
+    let points = cumulative_positives(scores, positives);
+    let (_, n_positives, _) = *points.last().expect("The curve has a starting point");
+    assert!(
+        n_positives > 0,
+        "Positive targets are needed, cannot calculate precision-recall curve"
+    );
+    PrecisionRecallCurve {
+        precisions: points
+            .iter()
+            .map(|(_, tp, fp)| match tp + fp {
+                0 => 1.0,
+                predicted => *tp as f64 / predicted as f64,
+            })
+            .collect(),
+        recalls: points
+            .iter()
+            .map(|(_, tp, _)| *tp as f64 / n_positives as f64)
+            .collect(),
+        thresholds: points.iter().map(|(threshold, _, _)| *threshold).collect(),
+    }
+}
+
+fn binary_roc_auc(scores: &[f64], positives: &[bool]) -> f64 {
+    let curve = binary_roc_curve(scores, positives);
+    // trapezoidal area under the curve, ties give a diagonal segment
+    curve
+        .false_positive_rates
+        .windows(2)
+        .zip(curve.true_positive_rates.windows(2))
+        .map(|(fpr, tpr)| (fpr[1] - fpr[0]) * (tpr[1] + tpr[0]) / 2.0)
+        .sum()
+}
+
+fn binary_average_precision(scores: &[f64], positives: &[bool]) -> f64 {
+    let curve = binary_precision_recall_curve(scores, positives);
+    curve
+        .recalls
+        .windows(2)
+        .zip(&curve.precisions[1..])
+        .map(|(recall, precision)| (recall[1] - recall[0]) * precision)
+        .sum()
+}
+
+/// Whether each target is the positive label
+fn is_positive<L: PartialEq>(targets: &[L], positive: &L) -> Vec<bool> {
+    targets.iter().map(|target| target == positive).collect()
+}
+
+/// Apply a binary ranking metric to the probabilities of each class
+fn one_vs_rest<L: PartialEq>(
+    probabilities: &[Vec<f64>],
+    targets: &[L],
+    classes: &[L],
+    average: &Average<L>,
+    binary_metric: fn(&[f64], &[bool]) -> f64,
+) -> f64 {
+    assert!(
+        probabilities.len() == targets.len(),
+        "Probabilities and targets are of differing length, cannot calculate metric"
+    );
+    assert!(
+        probabilities.iter().all(|row| row.len() == classes.len()),
+        "Expected a probability for each of the {} classes",
+        classes.len()
+    );
+    let class_scores =
+        |class: usize| -> Vec<f64> { probabilities.iter().map(|row| row[class]).collect() };
+    match average {
+        Average::Binary(positive) => {
+            let class = classes
+                .iter()
+                .position(|class| class == positive)
+                .expect("The positive label isn't one of the classes");
+            binary_metric(&class_scores(class), &is_positive(targets, positive))
+        }
+        Average::Micro => {
+            let scores = probabilities.iter().flatten().copied().collect::<Vec<_>>();
+            let positives = targets
+                .iter()
+                .flat_map(|target| classes.iter().map(move |class| class == target))
+                .collect::<Vec<_>>();
+            binary_metric(&scores, &positives)
+        }
+        Average::Macro | Average::Weighted => {
+            let mut total = 0.0;
+            let mut total_weight = 0.0;
+            for (i, class) in classes.iter().enumerate() {
+                let positives = is_positive(targets, class);
+                let weight = match average {
+                    Average::Weighted => {
+                        positives.iter().filter(|positive| **positive).count() as f64
+                    }
+                    _ => 1.0,
+                };
+                total += weight * binary_metric(&class_scores(i), &positives);
+                total_weight += weight;
+            }
+            total / total_weight
+        }
+    }
+}
+
+/// The receiver operating characteristic curve of scores for a positive label
+///
+/// # Arguments
+/// * `scores` - scores of each record, higher meaning more likely to be positive
+/// * `targets` - true labels
+/// * `positive` - the positive label
+///
+/// # Panics
+/// if the targets aren't a mix of positives and negatives
+pub fn roc_curve<L: PartialEq>(scores: &[f64], targets: &[L], positive: &L) -> RocCurve {
+    binary_roc_curve(scores, &is_positive(targets, positive))
+}
+
+/// The precision-recall curve of scores for a positive label
+///
+/// # Arguments
+/// * `scores` - scores of each record, higher meaning more likely to be positive
+/// * `targets` - true labels
+/// * `positive` - the positive label
+///
+/// # Panics
+/// if none of the targets are positive
+pub fn precision_recall_curve<L: PartialEq>(
+    scores: &[f64],
+    targets: &[L],
+    positive: &L,
+) -> PrecisionRecallCurve {
+    binary_precision_recall_curve(scores, &is_positive(targets, positive))
+}
+
+/// Area under the receiver operating characteristic curve, one-vs-rest for more than one class
+///
+/// # Arguments
+/// * `probabilities` - the probability of each class for each record, as from
+///   [`crate::Model::predict_proba`]
+/// * `targets` - true labels
+/// * `classes` - the class of each column of `probabilities`
+/// * `average` - `Binary` scores one class against the rest, `Micro` pools every class and
+///   `Macro` and `Weighted` average the score of each class against the rest
+///
+/// # Panics
+/// if a class being scored is missing from the targets or is every target
+pub fn roc_auc_score<L: PartialEq>(
+    probabilities: &[Vec<f64>],
+    targets: &[L],
+    classes: &[L],
+    average: &Average<L>,
+) -> f64 {
+    one_vs_rest(probabilities, targets, classes, average, binary_roc_auc)
+}
+
+/// Average precision, the area under the precision-recall curve as the precision at each
+/// threshold weighted by the increase in recall, one-vs-rest for more than one class
+///
+/// # Arguments
+/// * `probabilities` - the probability of each class for each record, as from
+///   [`crate::Model::predict_proba`]
+/// * `targets` - true labels
+/// * `classes` - the class of each column of `probabilities`
+/// * `average` - how the score of each class is combined, as for [`roc_auc_score`]
+///
+/// # Panics
+/// if a class being scored is missing from the targets
+pub fn average_precision_score<L: PartialEq>(
+    probabilities: &[Vec<f64>],
+    targets: &[L],
+    classes: &[L],
+    average: &Average<L>,
+) -> f64 {
+    one_vs_rest(
+        probabilities,
+        targets,
+        classes,
+        average,
+        binary_average_precision,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranking_metrics() {
+        let targets = [0, 0, 1, 1, 1];
+        let scores = [0.1, 0.4, 0.35, 0.8, 0.4];
+
+        let curve = roc_curve(&scores, &targets, &1);
+        assert_eq!(curve.thresholds, [f64::INFINITY, 0.8, 0.4, 0.35, 0.1]);
+        assert_eq!(curve.false_positive_rates, [0.0, 0.0, 0.5, 0.5, 1.0]);
+        assert_eq!(
+            curve.true_positive_rates,
+            [0.0, 1.0 / 3.0, 2.0 / 3.0, 1.0, 1.0]
+        );
+
+        let curve = precision_recall_curve(&scores, &targets, &1);
+        assert_eq!(curve.precisions, [1.0, 1.0, 2.0 / 3.0, 0.75, 0.6]);
+        assert_eq!(curve.recalls, [0.0, 1.0 / 3.0, 2.0 / 3.0, 1.0, 1.0]);
+
+        let probabilities = scores.iter().map(|p| vec![1.0 - p, *p]).collect::<Vec<_>>();
+        let auc = roc_auc_score(&probabilities, &targets, &[0, 1], &Average::Binary(1));
+        // 4 of the 6 positive-negative pairs are ordered correctly and one is tied
+        assert!((auc - 4.5 / 6.0).abs() < 1e-12);
+        let average_precision =
+            average_precision_score(&probabilities, &targets, &[0, 1], &Average::Binary(1));
+        assert!((average_precision - (1.0 + 2.0 / 3.0 + 0.75) / 3.0).abs() < 1e-12);
+
+        // with two classes each class against the rest has the same area
+        let macro_auc = roc_auc_score(&probabilities, &targets, &[0, 1], &Average::Macro);
+        assert!((macro_auc - auc).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,218 @@
+/// A metric to score a regression model's predictions with, greater is better
+///
+/// Errors and deviances are negated so that greater is better. Unlike [`Metric`](super::Metric)
+/// this isn't a [`Scorer`](super::Scorer), models predict [`Label`](crate::Label)s which can't be
+/// continuous values, so it scores predicted values directly with [`RegressionMetric::score`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegressionMetric {
+    NegMeanSquaredError,
+    NegRootMeanSquaredError,
+    NegMeanAbsoluteError,
+    NegMedianAbsoluteError,
+    R2,
+    ExplainedVariance,
+    NegMeanAbsolutePercentageError,
+    NegMeanPoissonDeviance,
+}
+
+impl RegressionMetric {
+    /// Score predictions against targets
+    ///
+    /// # Arguments
+    /// * `predictions` - predicted values
+    /// * `targets` - true values
+    pub fn score(&self, predictions: &[f64], targets: &[f64]) -> f64 {
+        match self {
+            RegressionMetric::NegMeanSquaredError => -mean_squared_error(predictions, targets),
+            RegressionMetric::NegRootMeanSquaredError => {
+                -root_mean_squared_error(predictions, targets)
+            }
+            RegressionMetric::NegMeanAbsoluteError => -mean_absolute_error(predictions, targets),
+            RegressionMetric::NegMedianAbsoluteError => {
+                -median_absolute_error(predictions, targets)
+            }
+            RegressionMetric::R2 => r2_score(predictions, targets),
+            RegressionMetric::ExplainedVariance => explained_variance_score(predictions, targets),
+            RegressionMetric::NegMeanAbsolutePercentageError => {
+                -mean_absolute_percentage_error(predictions, targets)
+            }
+            RegressionMetric::NegMeanPoissonDeviance => {
+                -mean_poisson_deviance(predictions, targets)
+            }
+        }
+    }
+}
+
+fn check_lengths(predictions: &[f64], targets: &[f64]) {
+    assert!(
+        predictions.len() == targets.len(),
+        "Predictions and targets are of differing length, cannot calculate metric"
+    );
+    assert!(!targets.is_empty(), "No targets, cannot calculate metric");
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (total, count) = values.fold((0.0, 0), |(total, count), value| (total + value, count + 1));
+    total / count as f64
+}
+
+/// The proportion of the variance of the targets left unexplained, 1 if there's no variance to
+/// explain and none is left or 0 if some is left
+fn unexplained_score(residual: f64, total: f64) -> f64 {
+    match total {
+        0.0 if residual == 0.0 => 1.0,
+        0.0 => 0.0,
+        _ => 1.0 - residual / total,
+    }
+}
+
+/// The mean of the squared differences between predictions and targets
+pub fn mean_squared_error(predictions: &[f64], targets: &[f64]) -> f64 {
+    check_lengths(predictions, targets);
+    mean(
+        predictions
+            .iter()
+            .zip(targets)
+            .map(|(p, t)| (p - t).powi(2)),
+    )
+}
+
+/// The square root of [`mean_squared_error`], in the units of the targets
+pub fn root_mean_squared_error(predictions: &[f64], targets: &[f64]) -> f64 {
+    mean_squared_error(predictions, targets).sqrt()
+}
+
+/// The mean of the absolute differences between predictions and targets
+pub fn mean_absolute_error(predictions: &[f64], targets: &[f64]) -> f64 {
+    check_lengths(predictions, targets);
+    mean(predictions.iter().zip(targets).map(|(p, t)| (p - t).abs()))
+}
+
+/// The median of the absolute differences between predictions and targets, robust to outliers
+pub fn median_absolute_error(predictions: &[f64], targets: &[f64]) -> f64 {
+    check_lengths(predictions, targets);
+    let mut errors = predictions
+        .iter()
+        .zip(targets)
+        .map(|(p, t)| (p - t).abs())
+        .collect::<Vec<_>>();
+    errors.sort_by(|a, b| a.total_cmp(b));
+    let middle = errors.len() / 2;
+    match errors.len() % 2 {
+        0 => (errors[middle - 1] + errors[middle]) / 2.0,
+        _ => errors[middle],
+    }
+}
+
+/// The coefficient of determination, the proportion of the variance of the targets explained by
+/// the predictions
+///
+/// # Returns
+/// 1 for perfect predictions, 0 for always predicting the mean target and negative for worse
+pub fn r2_score(predictions: &[f64], targets: &[f64]) -> f64 {
+    check_lengths(predictions, targets);
+    let target_mean = mean(targets.iter().copied());
+    let residual = predictions
+        .iter()
+        .zip(targets)
+        .map(|(p, t)| (t - p).powi(2))
+        .sum::<f64>();
+    let total = targets
+        .iter()
+        .map(|t| (t - target_mean).powi(2))
+        .sum::<f64>();
+    unexplained_score(residual, total)
+}
+
+/// The proportion of the variance of the targets explained by the predictions, like
+/// [`r2_score`] but ignoring any constant offset of the predictions
+pub fn explained_variance_score(predictions: &[f64], targets: &[f64]) -> f64 {
+    check_lengths(predictions, targets);
+    let variance = |values: &[f64]| {
+        let values_mean = mean(values.iter().copied());
+        mean(values.iter().map(|value| (value - values_mean).powi(2)))
+    };
+    let residuals = targets
+        .iter()
+        .zip(predictions)
+        .map(|(t, p)| t - p)
+        .collect::<Vec<_>>();
+    unexplained_score(variance(&residuals), variance(targets))
+}
+
+/// The mean absolute error relative to each target, as a fraction rather than a percentage
+///
+/// Targets of 0 are treated as [`f64::EPSILON`] so their errors are very large rather than
+/// infinite.
+pub fn mean_absolute_percentage_error(predictions: &[f64], targets: &[f64]) -> f64 {
+    check_lengths(predictions, targets);
+    mean(
+        predictions
+            .iter()
+            .zip(targets)
+            .map(|(p, t)| (p - t).abs() / t.abs().max(f64::EPSILON)),
+    )
+}
+
+/// The mean Poisson deviance, for predicting counts or rates
+///
+/// # Panics
+/// if any target is negative or any prediction isn't positive
+pub fn mean_poisson_deviance(predictions: &[f64], targets: &[f64]) -> f64 {
+    check_lengths(predictions, targets);
+    assert!(
+        targets.iter().all(|t| *t >= 0.0) && predictions.iter().all(|p| *p > 0.0),
+        "Poisson deviance needs non-negative targets and positive predictions"
+    );
+    mean(predictions.iter().zip(targets).map(|(p, t)| {
+        let log_term = match t {
+            0.0 => 0.0,
+            _ => t * (t / p).ln(),
+        };
+        2.0 * (log_term - t + p)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regression_metrics() {
+        let targets = [3.0, -0.5, 2.0, 7.0];
+        let predictions = [2.5, 0.0, 2.0, 8.0];
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-12;
+
+        assert!(close(mean_squared_error(&predictions, &targets), 0.375));
+        assert!(close(
+            root_mean_squared_error(&predictions, &targets),
+            0.375f64.sqrt()
+        ));
+        assert!(close(mean_absolute_error(&predictions, &targets), 0.5));
+        assert!(close(median_absolute_error(&predictions, &targets), 0.5));
+        assert!(close(
+            r2_score(&predictions, &targets),
+            0.948_608_137_044_967_9
+        ));
+        assert!(close(
+            explained_variance_score(&predictions, &targets),
+            0.957_173_447_537_473_2
+        ));
+        assert!(close(
+            mean_absolute_percentage_error(&predictions, &targets),
+            (0.5 / 3.0 + 1.0 + 0.0 + 1.0 / 7.0) / 4.0
+        ));
+        assert!(close(r2_score(&[1.0, 1.0], &[1.0, 1.0]), 1.0));
+
+        let counts = [0.0, 1.0, 4.0];
+        let rates = [0.5, 1.0, 2.0];
+        let expected = 2.0 * (0.5 + 0.0 + (4.0 * 2f64.ln() - 2.0)) / 3.0;
+        assert!(close(mean_poisson_deviance(&rates, &counts), expected));
+
+        assert_eq!(
+            RegressionMetric::NegMeanSquaredError.score(&predictions, &targets),
+            -0.375
+        );
+        assert_eq!(RegressionMetric::R2.score(&targets, &targets), 1.0);
+    }
+}
@@ -0,0 +1,145 @@
+use crate::data::Features;
+use crate::metrics::{
+    accuracy, average_precision_score, brier_score, f1_score, log_loss, precision, recall,
+    roc_auc_score, Metric,
+};
+use crate::{Label, Model};
+
+use async_trait::async_trait;
+use std::marker::PhantomData;
+
+/// Scores a fitted model, accepted anywhere a [`Metric`] is
+///
+/// Implement this for domain specific metrics such as expected profit. Scorers see the model
+/// itself so they can score predictions or predicted probabilities.
+#[async_trait]
+pub trait Scorer<L: Label = isize>: Send + Sync {
+    /// Score a fitted model on features and targets
+    async fn score(
+        &self,
+        model: &(dyn Model<L> + Sync),
+        features: &dyn Features,
+        targets: &[L],
+    ) -> f64;
+
+    /// Whether greater scores are better, otherwise the score is a loss
+    fn greater_is_better(&self) -> bool {
+        true
+    }
+
+    /// Whether `score` is at least as good as `other`
+    fn at_least_as_good(&self, score: f64, other: f64) -> bool {
+        match self.greater_is_better() {
+            true => score >= other,
+            false => score <= other,
+        }
+    }
+}
+
+// async_trait names the reference's lifetime, which clippy flags
+#[allow(clippy::needless_lifetimes)]
+#[async_trait]
+impl<L: Label, S: Scorer<L> + ?Sized> Scorer<L> for &S {
+    async fn score(
+        &self,
+        model: &(dyn Model<L> + Sync),
+        features: &dyn Features,
+        targets: &[L],
+    ) -> f64 {
+        (**self).score(model, features, targets).await
+    }
+
+    fn greater_is_better(&self) -> bool {
+        (**self).greater_is_better()
+    }
+
+    fn at_least_as_good(&self, score: f64, other: f64) -> bool {
+        (**self).at_least_as_good(score, other)
+    }
+}
+
+#[async_trait]
+impl<L: Label> Scorer<L> for Metric<L> {
+    async fn score(
+        &self,
+        model: &(dyn Model<L> + Sync),
+        features: &dyn Features,
+        targets: &[L],
+    ) -> f64 {
+        let classes = model.classes();
+        match self {
+            Metric::Accuracy => accuracy(&model.predict(features).await, targets),
+            Metric::F1Score(average) => f1_score(&model.predict(features).await, targets, average),
+            Metric::Precision(average) => {
+                precision(&model.predict(features).await, targets, average)
+            }
+            Metric::Recall(average) => recall(&model.predict(features).await, targets, average),
+            Metric::RocAuc(average) => roc_auc_score(
+                &model.predict_proba(features).await,
+                targets,
+                classes,
+                average,
+            ),
+            Metric::AveragePrecision(average) => average_precision_score(
+                &model.predict_proba(features).await,
+                targets,
+                classes,
+                average,
+            ),
+            Metric::NegLogLoss => {
+                -log_loss(&model.predict_proba(features).await, targets, classes)
+            }
+            Metric::NegBrierScore => {
+                -brier_score(&model.predict_proba(features).await, targets, classes)
+            }
+        }
+    }
+}
+
+/// A scorer computing a score from a model's predictions and the targets
+///
+/// # Arguments
+/// * `score_fn` - computes the score from the predictions and the targets
+/// * `greater_is_better` - whether greater scores are better
+pub struct PredictionScorer<L, F> {
+    score_fn: F,
+    greater_is_better: bool,
+    labels: PhantomData<fn(&[L])>,
+}
+
+/// Make a scorer from a function of a model's predictions and the targets
+///
+/// # Arguments
+/// * `score_fn` - computes the score from the predictions and the targets
+/// * `greater_is_better` - whether greater scores are better, `false` for losses
+pub fn make_scorer<L, F>(score_fn: F, greater_is_better: bool) -> PredictionScorer<L, F>
+where
+    L: Label,
+    F: Fn(&[L], &[L]) -> f64 + Send + Sync,
+{
+    PredictionScorer {
+        score_fn,
+        greater_is_better,
+        labels: PhantomData,
+    }
+}
+
+#[async_trait]
+impl<L, F> Scorer<L> for PredictionScorer<L, F>
+where
+    L: Label,
+    F: Fn(&[L], &[L]) -> f64 + Send + Sync,
+{
+    async fn score(
+        &self,
+        model: &(dyn Model<L> + Sync),
+        features: &dyn Features,
+        targets: &[L],
+    ) -> f64 {
+        (self.score_fn)(&model.predict(features).await, targets)
+    }
+
+    fn greater_is_better(&self) -> bool {
+        self.greater_is_better
+    }
+}
@@ -0,0 +1,378 @@
+use crate::data::Features;
+use crate::metrics::Scorer;
+use crate::{Label, Model};
+
+use futures::future::join_all;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::collections::BTreeMap;
+use std::panic::resume_unwind;
+use std::sync::Arc;
+use tokio::runtime::Handle;
+
+/// The records used to train and to validate a model in one round of cross-validation
+///
+/// # Arguments
+/// * `train` - the rows to fit on, ascending
+/// * `validation` - the rows to score on, ascending
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fold {
+    pub train: Vec<usize>,
+    pub validation: Vec<usize>,
+}
+
+impl Fold {
+    /// Train on every row not held out for validation
+    fn holding_out(mut validation: Vec<usize>, n_rows: usize) -> Self {
+        validation.sort_unstable();
+        let mut is_validation = vec![false; n_rows];
+        for row in &validation {
+            is_validation[*row] = true;
+        }
+        Self {
+            train: (0..n_rows).filter(|row| !is_validation[*row]).collect(),
+            validation,
+        }
+    }
+}
+
+/// A strategy for splitting records into folds for cross-validation
+pub trait CrossValidator<L: Label = isize>: Send + Sync {
+    /// Split the records into folds
+    ///
+    /// # Arguments
+    /// * `targets` - the target of every record
+    ///
+    /// # Returns
+    /// the train and validation rows of each fold
+    fn split(&self, targets: &[L]) -> Vec<Fold>;
+}
+
+/// Split records into `n_splits` consecutive folds, each validated on once
+///
+/// The first `n_rows % n_splits` folds get one more record than the rest.
+#[derive(Clone, Debug)]
+pub struct KFold {
+    n_splits: usize,
+    seed: Option<u64>,
+}
+
+impl KFold {
+    /// # Panics
+    /// if there are fewer than 2 splits
+    pub fn new(n_splits: usize) -> Self {
+        assert!(n_splits >= 2, "Need at least 2 splits, got {n_splits}");
+        Self {
+            n_splits,
+            seed: None,
+        }
+    }
+
+    /// Shuffle the records before splitting them into folds
+    pub fn with_shuffle(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+}
+
+impl<L: Label> CrossValidator<L> for KFold {
+    fn split(&self, targets: &[L]) -> Vec<Fold> {
+        let n_rows = targets.len();
+        assert!(
+            self.n_splits <= n_rows,
+            "Cannot split {n_rows} records into {} folds",
+            self.n_splits
+        );
+        let mut rows = (0..n_rows).collect::<Vec<_>>();
+        if let Some(seed) = self.seed {
+            rows.shuffle(&mut StdRng::seed_from_u64(seed));
+        }
+        let mut start = 0;
+        (0..self.n_splits)
+            .map(|fold| {
+                let fold_size = n_rows / self.n_splits + usize::from(fold < n_rows % self.n_splits);
+                let validation = rows[start..start + fold_size].to_vec();
+                start += fold_size;
+                Fold::holding_out(validation, n_rows)
+            })
+            .collect()
+    }
+}
+
+/// Split records into `n_splits` folds that each keep the proportion of every class
+///
+/// Records of each class are dealt out to the folds in turn, so fold sizes differ by at most
+/// one record.
+#[derive(Clone, Debug)]
+pub struct StratifiedKFold {
+    n_splits: usize,
+    seed: Option<u64>,
+}
+
+impl StratifiedKFold {
+    /// # Panics
+    /// if there are fewer than 2 splits
+    pub fn new(n_splits: usize) -> Self {
+        assert!(n_splits >= 2, "Need at least 2 splits, got {n_splits}");
+        Self {
+            n_splits,
+            seed: None,
+        }
+    }
+
+    /// Shuffle the records of each class before dealing them out to the folds
+    pub fn with_shuffle(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+}
+
+impl<L: Label> CrossValidator<L> for StratifiedKFold {
+    fn split(&self, targets: &[L]) -> Vec<Fold> {
+        assert!(
+            self.n_splits <= targets.len(),
+            "Cannot split {} records into {} folds",
+            targets.len(),
+            self.n_splits
+        );
+        let mut class_rows = BTreeMap::<&L, Vec<usize>>::new();
+        for (row, target) in targets.iter().enumerate() {
+            class_rows.entry(target).or_default().push(row);
+        }
+        let mut rng = self.seed.map(StdRng::seed_from_u64);
+        let mut validation = vec![vec![]; self.n_splits];
+        // carry on dealing from where the previous class stopped to keep the folds balanced
+        let mut position = 0;
+        for mut rows in class_rows.into_values() {
+            if let Some(rng) = rng.as_mut() {
+                rows.shuffle(rng);
+            }
+            for row in rows {
+                validation[position % self.n_splits].push(row);
+                position += 1;
+            }
+        }
+        validation
+            .into_iter()
+            .map(|rows| Fold::holding_out(rows, targets.len()))
+            .collect()
+    }
+}
+
+/// Split records into `n_splits` folds so that no group appears in more than one fold
+///
+/// Groups are placed largest first into whichever fold currently holds the fewest records.
+///
+/// # Arguments
+/// * `n_splits` - the number of folds
+/// * `groups` - the group of every record, e.g. the patient a measurement was taken from
+#[derive(Clone, Debug)]
+pub struct GroupKFold<G = usize> {
+    n_splits: usize,
+    groups: Vec<G>,
+}
+
+impl<G: Ord> GroupKFold<G> {
+    /// # Panics
+    /// if there are fewer than 2 splits
+    pub fn new(n_splits: usize, groups: Vec<G>) -> Self {
+        assert!(n_splits >= 2, "Need at least 2 splits, got {n_splits}");
+        Self { n_splits, groups }
+    }
+}
+
+impl<L: Label, G: Ord + Send + Sync> CrossValidator<L> for GroupKFold<G> {
+    fn split(&self, targets: &[L]) -> Vec<Fold> {
+        assert!(
+            self.groups.len() == targets.len(),
+            "Groups and targets are of differing length, cannot split"
+        );
+        let mut group_rows = BTreeMap::<&G, Vec<usize>>::new();
+        for (row, group) in self.groups.iter().enumerate() {
+            group_rows.entry(group).or_default().push(row);
+        }
+        assert!(
+            self.n_splits <= group_rows.len(),
+            "Cannot split {} groups into {} folds",
+            group_rows.len(),
+            self.n_splits
+        );
+        let mut group_rows = group_rows.into_values().collect::<Vec<_>>();
+        // stable, so equally sized groups keep their order
+        group_rows.sort_by_key(|rows| std::cmp::Reverse(rows.len()));
+
+        let mut validation = vec![vec![]; self.n_splits];
+        for rows in group_rows {
+            let lightest = (0..self.n_splits)
+                .min_by_key(|fold| validation[*fold].len())
+                .unwrap();
+            validation[lightest].extend(rows);
+        }
+        validation
+            .into_iter()
+            .map(|rows| Fold::holding_out(rows, targets.len()))
+            .collect()
+    }
+}
+
+/// Split time ordered records so that every fold is validated on records after those it trains on
+///
+/// The records are cut into `n_splits + 1` equal blocks, any remainder going to the first block.
+/// Fold `i` validates on block `i + 1` and trains on every block before it, so the training set
+/// grows with each fold.
+#[derive(Clone, Debug)]
+pub struct TimeSeriesSplit {
+    n_splits: usize,
+}
+
+impl TimeSeriesSplit {
+    /// # Panics
+    /// if there are fewer than 2 splits
+    pub fn new(n_splits: usize) -> Self {
+        assert!(n_splits >= 2, "Need at least 2 splits, got {n_splits}");
+        Self { n_splits }
+    }
+}
+
+impl<L: Label> CrossValidator<L> for TimeSeriesSplit {
+    fn split(&self, targets: &[L]) -> Vec<Fold> {
+        let n_rows = targets.len();
+        let validation_size = n_rows / (self.n_splits + 1);
+        assert!(
+            validation_size > 0,
+            "Cannot split {n_rows} records into {} folds",
+            self.n_splits
+        );
+        let first_validation = n_rows - self.n_splits * validation_size;
+        (0..self.n_splits)
+            .map(|fold| {
+                let start = first_validation + fold * validation_size;
+                Fold {
+                    train: (0..start).collect(),
+                    validation: (start..start + validation_size).collect(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Estimate how well a model generalises by fitting and scoring it on each fold
+///
+/// A fresh model is fit on the training rows of every fold and scored on its validation rows.
+/// Each fold is fit on its own thread from Tokio's blocking pool so folds are fit in parallel,
+/// with each fold's training and validation data built on its thread.
+///
+/// # Arguments
+/// * `model_factory` - builds an unfitted model for each fold
+/// * `features` - the features of every record
+/// * `targets` - the target of every record
+/// * `cv` - the strategy for splitting records into folds
+/// * `scorer` - the metric or scorer to score each fold with
+///
+/// # Returns
+/// the score of each fold, in the order the folds were produced
+///
+/// # Panics
+/// if called outside a Tokio runtime, or fitting or scoring a fold panics
+pub async fn cross_val_score<M, L, S, F>(
+    model_factory: F,
+    features: &dyn Features,
+    targets: &[L],
+    cv: &dyn CrossValidator<L>,
+    scorer: S,
+) -> Vec<f64>
+where
+    M: Model<L> + Send + Sync + 'static,
+    L: Label + 'static,
+    S: Scorer<L> + 'static,
+    F: Fn() -> M,
+{
+    assert!(
+        features.n_rows() == targets.len(),
+        "Features and targets are of differing length, cannot cross-validate"
+    );
+    let folds = cv.split(targets);
+    let features = Arc::new(features.to_dataset().into_owned());
+    let targets: Arc<[L]> = Arc::from(targets);
+    let scorer = Arc::new(scorer);
+    let runtime = Handle::current();
+
+    let fold_tasks = folds.into_iter().map(|fold| {
+        let mut model = model_factory();
+        let features = Arc::clone(&features);
+        let targets = Arc::clone(&targets);
+        let scorer = Arc::clone(&scorer);
+        let runtime = runtime.clone();
+        tokio::task::spawn_blocking(move || {
+            let select_targets = |rows: &[usize]| {
+                rows.iter()
+                    .map(|row| targets[*row].clone())
+                    .collect::<Vec<_>>()
+            };
+            let train_features = features.select_rows(&fold.train);
+            let train_targets = select_targets(&fold.train);
+            let validation_features = features.select_rows(&fold.validation);
+            let validation_targets = select_targets(&fold.validation);
+            runtime.block_on(async move {
+                model.fit(&train_features, &train_targets).await;
+                model
+                    .score(&validation_features, &validation_targets, scorer.as_ref())
+                    .await
+            })
+        })
+    });
+    join_all(fold_tasks)
+        .await
+        .into_iter()
+        .map(|score| score.unwrap_or_else(|error| resume_unwind(error.into_panic())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_splitters() {
+        let targets = vec![0, 0, 0, 0, 0, 0, 1, 1, 1, 1];
+        let groups = vec![0, 0, 0, 1, 1, 2, 2, 3, 4, 4];
+        let splitters: Vec<Box<dyn CrossValidator>> = vec![
+            Box::new(KFold::new(3)),
+            Box::new(KFold::new(3).with_shuffle(1)),
+            Box::new(StratifiedKFold::new(2).with_shuffle(1)),
+            Box::new(GroupKFold::new(3, groups.clone())),
+        ];
+        for splitter in splitters {
+            let folds = splitter.split(&targets);
+            let mut validated = folds
+                .iter()
+                .flat_map(|fold| fold.validation.clone())
+                .collect::<Vec<_>>();
+            validated.sort_unstable();
+            assert_eq!(validated, (0..targets.len()).collect::<Vec<_>>());
+            for fold in &folds {
+                assert_eq!(fold.train.len() + fold.validation.len(), targets.len());
+                assert!(fold.train.iter().all(|row| !fold.validation.contains(row)));
+            }
+        }
+
+        let folds = StratifiedKFold::new(2).split(&targets);
+        assert!(folds.iter().all(|fold| fold
+            .validation
+            .iter()
+            .filter(|row| targets[**row] == 1)
+            .count()
+            == 2));
+        for fold in GroupKFold::new(3, groups.clone()).split(&targets) {
+            assert!(fold.train.iter().all(|train| fold
+                .validation
+                .iter()
+                .all(|row| groups[*row] != groups[*train])));
+        }
+        let folds = TimeSeriesSplit::new(3).split(&targets);
+        assert_eq!(folds[0].train, vec![0, 1, 2, 3]);
+        assert_eq!(folds[0].validation, vec![4, 5]);
+        assert_eq!(folds[2].train.len(), 8);
+    }
+}
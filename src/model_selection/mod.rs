@@ -0,0 +1,4 @@
+mod cross_validation;
+mod train_test_split;
+pub use cross_validation::*;
+pub use train_test_split::*;
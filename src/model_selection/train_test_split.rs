@@ -0,0 +1,236 @@
+use crate::data::{Dataset, Features};
+use crate::Label;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::collections::BTreeMap;
+
+/// Features and targets partitioned into a training and a test set
+///
+/// # Arguments
+/// * `train_features` - features of the training records
+/// * `test_features` - features of the test records
+/// * `train_targets` - targets of the training records
+/// * `test_targets` - targets of the test records
+#[derive(Clone, Debug)]
+pub struct TrainTestSplit<L = isize> {
+    pub train_features: Dataset,
+    pub test_features: Dataset,
+    pub train_targets: Vec<L>,
+    pub test_targets: Vec<L>,
+}
+
+/// Randomly partition records into a training and a test set
+///
+/// Records keep their original order within each set.
+///
+/// # Arguments
+/// * `features` - the features to split
+/// * `targets` - the targets to split
+/// * `test_size` - the proportion of records in the test set, rounded up to a whole record
+/// * `stratify` - keep the proportion of each class the same in both sets, as near as whole
+///   records allow while leaving every class at least one record in each set
+/// * `seed` - seed for the shuffle
+///
+/// # Returns
+/// the four partitions
+///
+/// # Panics
+/// if `test_size` isn't between 0 and 1, either set would be empty, or `stratify` is set and a
+/// class has fewer than two records or there are fewer test records than classes
+pub fn train_test_split<L: Label>(
+    features: &dyn Features,
+    targets: &[L],
+    test_size: f64,
+    stratify: bool,
+    seed: u64,
+) -> TrainTestSplit<L> {
+    assert!(
+        features.n_rows() == targets.len(),
+        "Features and targets are of differing length, cannot split"
+    );
+    assert!(
+        test_size > 0.0 && test_size < 1.0,
+        "Test size must be between 0 and 1, got {test_size}"
+    );
+    let n_test = (test_size * targets.len() as f64).ceil() as usize;
+    assert!(
+        n_test < targets.len(),
+        "A test size of {test_size} leaves no training records out of {}",
+        targets.len()
+    );
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut test_rows = match stratify {
+        false => {
+            let mut rows = (0..targets.len()).collect::<Vec<_>>();
+            rows.shuffle(&mut rng);
+            rows.truncate(n_test);
+            rows
+        }
+        true => stratified_test_rows(targets, n_test, &mut rng),
+    };
+    test_rows.sort_unstable();
+    let mut is_test = vec![false; targets.len()];
+    for row in &test_rows {
+        is_test[*row] = true;
+    }
+    let train_rows = (0..targets.len())
+        .filter(|row| !is_test[*row])
+        .collect::<Vec<_>>();
+
+    let features = features.to_dataset();
+    let select_targets = |rows: &[usize]| rows.iter().map(|row| targets[*row].clone()).collect();
+    TrainTestSplit {
+        train_features: features.select_rows(&train_rows),
+        test_features: features.select_rows(&test_rows),
+        train_targets: select_targets(&train_rows),
+        test_targets: select_targets(&test_rows),
+    }
+}
+
+/// Pick test records from each class in proportion to its size
+///
+/// Each class gets one test record and its proportion of the rest of `n_test` rounded down, with
+/// the records left over going to the classes with the largest remainders. No class gives all its
+/// records to the test set, so fewer than `n_test` records are picked if the classes have no more
+/// to spare.
+fn stratified_test_rows<L: Label>(targets: &[L], n_test: usize, rng: &mut StdRng) -> Vec<usize> {
+    let mut class_rows = BTreeMap::<&L, Vec<usize>>::new();
+    for (row, target) in targets.iter().enumerate() {
+        class_rows.entry(target).or_default().push(row);
+    }
+    if let Some((class, _)) = class_rows.iter().find(|(_, rows)| rows.len() < 2) {
+        panic!(
+            "Class {class:?} has a single record, it can't be in both the training and test sets"
+        );
+    }
+    assert!(
+        n_test >= class_rows.len(),
+        "{n_test} test records can't hold a record of each of the {} classes",
+        class_rows.len()
+    );
+
+    let class_sizes = class_rows.values().map(Vec::len).collect::<Vec<_>>();
+    let n_shared = n_test - class_rows.len();
+    let shares = class_sizes
+        .iter()
+        .map(|size| *size as f64 * n_shared as f64 / targets.len() as f64)
+        .collect::<Vec<_>>();
+    let mut class_n_test = shares
+        .iter()
+        .zip(&class_sizes)
+        .map(|(share, size)| (1 + share.floor() as usize).min(size - 1))
+        .collect::<Vec<_>>();
+    let mut by_remainder = (0..shares.len()).collect::<Vec<_>>();
+    by_remainder.sort_by(|a, b| {
+        (shares[*b] - shares[*b].floor()).total_cmp(&(shares[*a] - shares[*a].floor()))
+    });
+    let mut n_left_over = n_test - class_n_test.iter().sum::<usize>();
+    for class in by_remainder {
+        if n_left_over > 0 && class_n_test[class] < class_sizes[class] - 1 {
+            class_n_test[class] += 1;
+            n_left_over -= 1;
+        }
+    }
+
+    class_rows
+        .into_values()
+        .zip(class_n_test)
+        .flat_map(|(mut rows, n_test)| {
+            rows.shuffle(rng);
+            rows.truncate(n_test);
+            rows
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::load_milk_train_dataset;
+
+    #[test]
+    fn test_train_test_split() {
+        let (features, targets) = load_milk_train_dataset();
+        let class_proportions = |targets: &[isize]| {
+            let mut counts = BTreeMap::new();
+            for target in targets {
+                *counts.entry(*target).or_insert(0.0) += 1.0 / targets.len() as f64;
+            }
+            counts
+        };
+
+        let split = train_test_split(&features, &targets, 0.25, true, 7);
+        let n_test = (features.len() as f64 * 0.25).ceil() as usize;
+        assert_eq!(split.test_targets.len(), n_test);
+        assert_eq!(split.train_features.n_rows(), features.len() - n_test);
+        assert_eq!(split.test_features.n_columns(), 7);
+        let expected = class_proportions(&targets);
+        for proportions in [
+            class_proportions(&split.train_targets),
+            class_proportions(&split.test_targets),
+        ] {
+            for (class, proportion) in proportions {
+                assert!((proportion - expected[&class]).abs() < 0.01);
+            }
+        }
+        let rows = (0..features.len()).collect::<Vec<_>>();
+        let row_split = train_test_split(&features, &rows, 0.25, false, 7);
+        let first_test_row = row_split.test_targets[0];
+        assert_eq!(row_split.test_features.row(0), features[first_test_row]);
+        assert!(row_split
+            .test_targets
+            .windows(2)
+            .all(|pair| pair[0] < pair[1]));
+
+        let again = train_test_split(&features, &targets, 0.25, true, 7);
+        assert_eq!(again.test_features, split.test_features);
+        let reseeded = train_test_split(&features, &targets, 0.25, true, 8);
+        assert_ne!(reseeded.test_features, split.test_features);
+    }
+
+    #[test]
+    fn test_stratified_split_keeps_every_class_in_both_sets() {
+        let features = vec![vec![0.0]; 5];
+        let targets = [0, 0, 0, 1, 1];
+        // four test records would take every record of one of the classes
+        let split = train_test_split(&features, &targets, 0.8, true, 0);
+        assert_eq!(split.test_targets.len(), 3);
+        for class in [0, 1] {
+            assert!(split.train_targets.contains(&class));
+            assert!(split.test_targets.contains(&class));
+        }
+    }
+
+    #[test]
+    fn test_stratified_split_rare_class() {
+        let mut targets = vec![0; 100];
+        targets.extend([1, 1]);
+        let features = vec![vec![0.0]; targets.len()];
+        // the rare class's share of the test set rounds down to nothing
+        let split = train_test_split(&features, &targets, 0.1, true, 0);
+        assert_eq!(split.test_targets.len(), 11);
+        assert_eq!(split.test_targets.iter().filter(|target| **target == 1).count(), 1);
+        assert_eq!(split.train_targets.iter().filter(|target| **target == 1).count(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "can't hold a record of each")]
+    fn test_stratify_fewer_test_records_than_classes() {
+        train_test_split(&vec![vec![0.0]; 6], &[0, 0, 1, 1, 2, 2], 0.2, true, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "leaves no training records")]
+    fn test_empty_training_set() {
+        train_test_split(&vec![vec![0.0]; 3], &[0, 1, 0], 0.9, false, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "has a single record")]
+    fn test_stratify_single_record_class() {
+        train_test_split(&vec![vec![0.0]; 4], &[0, 0, 0, 1], 0.5, true, 0);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::Label;
+
+/// Encodes class labels as the integers `0..n_classes`, in the order of the sorted labels
+#[derive(Clone, Debug)]
+pub struct LabelEncoder<L> {
+    classes: Vec<L>,
+}
+
+impl<L> Default for LabelEncoder<L> {
+    fn default() -> Self {
+        Self { classes: vec![] }
+    }
+}
+
+impl<L: Label> LabelEncoder<L> {
+    /// Learn the classes present in a set of labels
+    pub fn fit(labels: &[L]) -> Self {
+        let mut classes = labels.to_vec();
+        classes.sort();
+        classes.dedup();
+        Self { classes }
+    }
+
+    /// The classes the encoder knows about, in encoded order
+    pub fn classes(&self) -> &[L] {
+        &self.classes
+    }
+
+    /// The encoding of a label, `None` if it wasn't seen when fitting
+    pub fn encode(&self, label: &L) -> Option<isize> {
+        self.classes.binary_search(label).ok().map(|index| index as isize)
+    }
+
+    /// The label an encoding represents
+    pub fn decode(&self, class: isize) -> &L {
+        &self.classes[class as usize]
+    }
+
+    /// Encode a set of labels
+    ///
+    /// # Panics
+    /// if any label wasn't seen when fitting
+    pub fn transform(&self, labels: &[L]) -> Vec<isize> {
+        labels
+            .iter()
+            .map(|label| {
+                self.encode(label)
+                    .unwrap_or_else(|| panic!("Label {label:?} wasn't seen when fitting the encoder"))
+            })
+            .collect()
+    }
+
+    /// Decode a set of encoded labels
+    pub fn inverse_transform(&self, classes: &[isize]) -> Vec<L> {
+        classes.iter().map(|class| self.decode(*class).clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_label_encoder() {
+        let labels = ["medium", "low", "high", "low"];
+        let encoder = LabelEncoder::fit(&labels);
+        assert_eq!(encoder.classes(), ["high", "low", "medium"]);
+        assert_eq!(encoder.encode(&"low"), Some(1));
+        assert_eq!(encoder.encode(&"unknown"), None);
+        assert_eq!(*encoder.decode(2), "medium");
+
+        let encoded = encoder.transform(&labels);
+        assert_eq!(encoded, [2, 1, 0, 1]);
+        assert_eq!(encoder.inverse_transform(&encoded), labels);
+    }
+
+    #[test]
+    #[should_panic(expected = "wasn't seen when fitting")]
+    fn test_transform_unseen_label() {
+        LabelEncoder::fit(&[0, 1]).transform(&[2]);
+    }
+}
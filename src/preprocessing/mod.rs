@@ -0,0 +1,2 @@
+mod label_encoder;
+pub use label_encoder::*;
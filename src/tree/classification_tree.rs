@@ -1,91 +1,335 @@
-use std::array::from_ref;
 use crate::loss_functions::LossFunction;
-use crate::tree::tree_core::{ChildType, Node, NodeType};
-use crate::tree::tree_utils::TreeSplit;
+use crate::tree::cost_matrix::{CostMatrix, CostMatrixError};
+use crate::tree::tree_core::{CategorySet, ChildType, NodeType};
+use crate::tree::tree_utils::{TrainingData, TrainingFeatures, TreeSplit};
 use crate::tree::{
     tree_core::{Arena, NodeId},
     tree_utils::{create_node_data, generate_splits, pick_best_split},
 };
-use crate::Model;
-use crate::metrics::{Metric, accuracy};
+use crate::inspection::{tree_shap, ShapValues};
+use crate::data::Features;
+use crate::preprocessing::LabelEncoder;
+use crate::{Label, Model};
 
 use async_trait::async_trait;
 use async_recursion::async_recursion;
 use derive_builder::Builder;
 use futures::future::join_all;
-use std::time::Instant;
-use std::sync::Arc;
+use std::collections::BTreeMap;
 
+/// A decision tree classifier
+///
+/// Targets can be any [`Label`], they're encoded as `0..n_classes` while fitting and the
+/// `encoded_prediction` and `encoded_class_counts` held by each node refer to these encodings,
+/// decode them with [`ClassificationTree::label_encoder`].
 #[derive(Default, Builder, Debug, Clone)]
-pub struct ClassificationTree {
+pub struct ClassificationTree<L: Label = isize> {
     #[builder(default)]
     pub loss_fn: LossFunction,
     #[builder(default = "2")]
     pub max_depth: usize,
     #[builder(default = "10")]
     pub min_samples_per_node: usize,
+    /// The minimum fraction of the total sample weight required in each child of a split
+    #[builder(default)]
+    pub min_weight_fraction_leaf: f64,
+    /// Weights applied to the records of each class, on top of any sample weights
+    #[builder(setter(strip_option), default)]
+    pub class_weight: Option<ClassWeight<L>>,
+    /// Misclassification costs, when set splits are chosen by cost weighted gini impurity and
+    /// leaves predict the class with the lowest expected cost rather than the majority class
+    #[builder(setter(strip_option), default)]
+    pub cost_matrix: Option<CostMatrix<L>>,
+    /// Columns holding categories, encoded as non-negative integers, rather than ordered values
+    #[builder(default)]
+    pub categorical_features: Vec<usize>,
     #[builder(default)]
     pub nodes: Arena,
+    #[builder(setter(skip))]
+    n_features: usize,
+    #[builder(setter(skip))]
+    feature_names: Option<Vec<String>>,
+    #[builder(setter(skip))]
+    label_encoder: LabelEncoder<L>,
 }
 
+/// How records of each class are weighted when fitting a tree
+#[derive(Clone, Debug)]
+pub enum ClassWeight<L> {
+    /// An explicit weight for each class, classes without a weight are weighted one
+    Explicit(BTreeMap<L, f64>),
+    /// Weight each class inversely proportional to its frequency so every class carries the same
+    /// total weight, `n_samples / (n_classes * n_class_samples)`
+    Balanced,
+}
 
+impl<L: Label> ClassWeight<L> {
+    /// The weight of each class present in `targets`
+    ///
+    /// # Panics
+    /// if an explicit weight is negative or isn't finite
+    pub fn weights(&self, targets: &[L]) -> BTreeMap<L, f64> {
+        if let ClassWeight::Explicit(weights) = self {
+            if let Some((class, weight)) = weights
+                .iter()
+                .find(|(_, weight)| !(weight.is_finite() && **weight >= 0.0))
+            {
+                panic!(
+                    "Class {class:?} has weight {weight}, class weights must be finite and \
+                     non-negative"
+                );
+            }
+        }
+        let mut class_frequencies = BTreeMap::new();
+        for target in targets {
+            *class_frequencies.entry(target.clone()).or_insert(0usize) += 1;
+        }
+        let n_classes = class_frequencies.len() as f64;
+        class_frequencies
+            .into_iter()
+            .map(|(class, frequency)| {
+                let weight = match self {
+                    ClassWeight::Explicit(weights) => weights.get(&class).copied().unwrap_or(1.0),
+                    ClassWeight::Balanced => targets.len() as f64 / (n_classes * frequency as f64),
+                };
+                (class, weight)
+            })
+            .collect()
+    }
+}
 
-impl ClassificationTree {
-    fn new(loss_fn: LossFunction, max_depth: usize, min_samples_per_node: usize) -> Self {
+impl<L: Label> ClassificationTree<L> {
+    pub fn new(loss_fn: LossFunction, max_depth: usize, min_samples_per_node: usize) -> Self {
         Self {
             loss_fn,
             max_depth,
             min_samples_per_node,
-            nodes: Arena { nodes: vec![] },
+            min_weight_fraction_leaf: 0.0,
+            class_weight: None,
+            cost_matrix: None,
+            categorical_features: vec![],
+            nodes: Arena::default(),
+            n_features: 0,
+            feature_names: None,
+            label_encoder: LabelEncoder::default(),
+        }
+    }
+
+    /// The names of the features the tree was fit on, if they were named
+    pub fn feature_names(&self) -> Option<&[String]> {
+        self.feature_names.as_deref()
+    }
+
+    /// The encoding of the classes the tree was fit on
+    pub fn label_encoder(&self) -> &LabelEncoder<L> {
+        &self.label_encoder
+    }
+
+    /// Fit the tree with each record contributing its weight to class counts, impurities and
+    /// leaf predictions, multiplied by the weight of its class if `class_weight` is set
+    ///
+    /// # Arguments
+    /// * `features` - training features
+    /// * `targets` - training targets
+    /// * `sample_weights` - the non-negative weight of each record
+    ///
+    /// # Panics
+    /// if `features`, `targets` and `sample_weights` are of differing lengths, a sample or class
+    /// weight is negative or isn't finite, or `cost_matrix` is set and doesn't cover every class in
+    /// `targets`
+    pub async fn fit_weighted(
+        &mut self,
+        features: &dyn Features,
+        targets: &[L],
+        sample_weights: &[f64],
+    ) {
+        assert!(
+            features.n_rows() == targets.len(),
+            "Features and targets are of differing length, cannot fit tree"
+        );
+        assert!(
+            sample_weights.len() == targets.len(),
+            "Sample weights and targets are of differing length, cannot fit tree"
+        );
+        assert!(
+            sample_weights.iter().all(|weight| *weight >= 0.0),
+            "Sample weights must be non-negative"
+        );
+        let sample_weights = match &self.class_weight {
+            Some(class_weight) => {
+                let class_weights = class_weight.weights(targets);
+                sample_weights
+                    .iter()
+                    .zip(targets)
+                    .map(|(weight, target)| weight * class_weights[target])
+                    .collect()
+            }
+            None => sample_weights.to_vec(),
+        };
+        assert!(
+            sample_weights.iter().all(|weight| weight.is_finite()),
+            "Sample weights multiplied by class weights must be finite"
+        );
+        self.feature_names = features.feature_names().map(<[String]>::to_vec);
+        let features = TrainingFeatures::new(features);
+        self.label_encoder = LabelEncoder::fit(targets);
+        let encoded_targets = self.label_encoder.transform(targets);
+        let costs = self.cost_matrix.as_ref().map(|cost_matrix| {
+            cost_matrix
+                .encode(&self.label_encoder)
+                .unwrap_or_else(|error| panic!("{error}, cannot fit tree"))
+        });
+        let data = TrainingData {
+            features: &features,
+            targets: &encoded_targets,
+            sample_weights: &sample_weights,
+            costs: costs.as_deref(),
+        };
+        let min_leaf_weight = self.min_weight_fraction_leaf * sample_weights.iter().sum::<f64>();
+        self.n_features = features.n_columns();
+        // refitting replaces any existing tree
+        self.nodes = Arena::default();
+
+        let parent_node: Option<NodeId> = None;
+        // calculate all splits just once
+        let all_splits = generate_splits(&features, &self.categorical_features).await;
+
+        // find the root node of the tree
+        let root_best_split =
+            pick_best_split(
+                &all_splits,
+                &data,
+                &self.loss_fn,
+                None,
+                &self.categorical_features,
+                min_leaf_weight,
+            )
+            .await;
+        if let Some(ref root_split) = root_best_split {
+            let root_node_data = create_node_data(root_split).await;
+            let root_node_type = root_node_data.node_type;
+            let root_node = self.nodes.add_new_node(root_node_data, &parent_node, None);
+
+            match root_node_type {
+                NodeType::Leaf => (),
+                NodeType::Branch => self.build_child_nodes(
+                    Some(&root_split.node_1_indices),
+                    Some(&root_split.node_2_indices),
+                    &all_splits,
+                    &data,
+                    min_leaf_weight,
+                    &Some(root_node),
+                ).await,
+            }
+        } else {
+            panic!("Couldn't find a good split when searching, this occurred when trying to build the root node of the tress")
+        }
+    }
+
+    /// Impurity-based feature importances of a fitted tree
+    ///
+    /// The importance of a feature is the total decrease in impurity, weighted by the total weight
+    /// of the training records reaching each node, across all branches that split on it. Importances
+    /// are normalised to sum to one.
+    ///
+    /// # Returns
+    /// the importance of each feature, indexed by column
+    pub fn feature_importances(&self) -> Vec<f64> {
+        let mut importances = vec![0f64; self.n_features];
+        let Some(root) = self.nodes.root() else {
+            return importances;
+        };
+        for node_id in self.nodes.depth_first(root) {
+            let data = &self.nodes.nodes[node_id.index].data;
+            if data.node_type == NodeType::Branch {
+                importances[data.column] += data.weighted_n_samples * (data.impurity - data.loss);
+            }
         }
+        let total: f64 = importances.iter().sum();
+        if total > 0.0 {
+            importances.iter_mut().for_each(|importance| *importance /= total);
+        }
+        importances
     }
 
+    /// Exact TreeSHAP explanations of the predicted probability of a class
+    ///
+    /// # Arguments
+    /// * `features` - the records to explain
+    /// * `class` - the class whose probability is explained
+    ///
+    /// # Returns
+    /// the SHAP values of each record
+    pub fn shap_values(&self, features: &dyn Features, class: &L) -> ShapValues {
+        let class = self.label_encoder.encode(class);
+        let n_classes = self.label_encoder.classes().len() as f64;
+        tree_shap([(&self.nodes, 1.0)], features, |data| {
+            let Some(class) = class else {
+                return 0.0;
+            };
+            // leaves no weight reached have no class distribution to go on
+            if data.weighted_n_samples == 0.0 {
+                return 1.0 / n_classes;
+            }
+            let n_class = data.encoded_class_counts.get(&class).copied().unwrap_or_default();
+            n_class / data.weighted_n_samples
+        })
+    }
 
     #[async_recursion]
     async fn build_child_nodes(
         &mut self,
         child_node_1_filter: Option<&'async_recursion [usize]>,
         child_node_2_filter: Option<&'async_recursion [usize]>,
-        all_splits: &Vec<TreeSplit>,
-        features: &[Vec<f64>],
-        targets: &[isize],
-        loss_fn: &LossFunction,
+        all_splits: &[TreeSplit],
+        data: &TrainingData<'_>,
+        min_leaf_weight: f64,
         parent_node: &Option<NodeId>,
     ) {
         // first child node
         let best_split_child_1 =
-            pick_best_split(&all_splits, features, targets, loss_fn, child_node_1_filter).await;
+            pick_best_split(
+                all_splits,
+                data,
+                &self.loss_fn,
+                child_node_1_filter,
+                &self.categorical_features,
+                min_leaf_weight,
+            )
+            .await;
         let new_node_data_child_1 = create_node_data(&best_split_child_1.clone().unwrap()).await;
+        let node_type_child_1 = new_node_data_child_1.node_type;
         // add new node as a child of the root node
         let new_node_id_child_1 =
             self.nodes
                 .add_new_node(new_node_data_child_1, parent_node, Some(ChildType::First));
 
         // recursive call
-        match new_node_data_child_1.node_type {
+        match node_type_child_1 {
             NodeType::Leaf => (),
             NodeType::Branch => self.build_child_nodes(
                 Some(&best_split_child_1.as_ref().unwrap().node_1_indices),
                 Some(&best_split_child_1.as_ref().unwrap().node_2_indices),
-                &all_splits,
-                features,
-                targets,
-                &self.loss_fn.clone(),
+                all_splits,
+                data,
+                min_leaf_weight,
                 &Some(new_node_id_child_1),
             ).await,
         }
 
         // second child none
         let best_split_child_2 = pick_best_split(
-            &all_splits,
-            features,
-            targets,
+            all_splits,
+            data,
             &self.loss_fn,
             child_node_2_filter,
+            &self.categorical_features,
+            min_leaf_weight,
         )
             .await;
 
         let new_node_data_child_2 = create_node_data(&best_split_child_2.clone().unwrap()).await;
+        let node_type_child_2 = new_node_data_child_2.node_type;
         let new_node_id_child_2 = self.nodes.add_new_node(
             new_node_data_child_2,
             parent_node,
@@ -93,99 +337,218 @@ impl ClassificationTree {
         );
 
         // recursive call
-        match new_node_data_child_2.node_type {
+        match node_type_child_2 {
             NodeType::Leaf => (),
             NodeType::Branch => self.build_child_nodes(
                 Some(&best_split_child_2.as_ref().unwrap().node_1_indices),
                 Some(&best_split_child_2.as_ref().unwrap().node_2_indices),
-                &all_splits,
-                features,
-                targets,
-                &self.loss_fn.clone(),
+                all_splits,
+                data,
+                min_leaf_weight,
                 &Some(new_node_id_child_2),
             ).await,
         }
     }
 
-    #[async_recursion]
-    async fn navigate_tree(&self, record: &[f64], current_node: &Node) -> isize {
-        let mut child_node_id: &Option<NodeId>;
-        if record[current_node.data.column] > current_node.data.value {
-            child_node_id = &current_node.first_child;
-        } else {
-            child_node_id = &current_node.second_child;
-        }
-        if let Some(child_node) = child_node_id {
-            let next_node = &self.nodes.nodes[child_node.index];
-            self.navigate_tree(record, next_node).await
-        } else {
-            current_node.data.majority_class
-        }
+    /// Predict the class with the lowest expected cost under the class distribution of each
+    /// record's leaf
+    ///
+    /// # Arguments
+    /// * `features` - the records to predict
+    /// * `cost_matrix` - misclassification costs, covering every class the tree was fit on
+    ///
+    /// # Returns
+    /// the predicted class of each record
+    ///
+    /// # Errors
+    /// if `cost_matrix` doesn't cover every class the tree was fit on
+    pub async fn predict_min_cost(
+        &self,
+        features: &dyn Features,
+        cost_matrix: &CostMatrix<L>,
+    ) -> Result<Vec<L>, CostMatrixError<L>> {
+        let pred_futures = (0..features.n_rows())
+            .map(|row| async move {
+                let leaf = self.navigate_tree(features, row, None);
+                self.min_cost_class(leaf, cost_matrix)
+            })
+            .collect::<Vec<_>>();
+        join_all(pred_futures).await.into_iter().collect()
     }
-}
 
-#[async_trait]
-impl Model for ClassificationTree {
-    async fn fit(&mut self, features: &[Vec<f64>], targets: &[isize]) {
-        let mut current_depth = 0usize;
-        // todo add data validation
+    /// The leaf each record lands in
+    ///
+    /// # Arguments
+    /// * `features` - the records to route through the tree
+    ///
+    /// # Returns
+    /// the id of the leaf node reached by each record
+    pub async fn apply(&self, features: &dyn Features) -> Vec<NodeId> {
+        let leaf_futures = (0..features.n_rows())
+            .map(|row| async move { self.navigate_tree(features, row, None) })
+            .collect::<Vec<_>>();
+        join_all(leaf_futures).await
+    }
 
-        let mut parent_node: Option<NodeId> = None;
-        // calculate all splits just once
-        let all_splits = generate_splits(features).await;
+    /// The nodes each record passes through and the comparisons made along the way
+    ///
+    /// # Arguments
+    /// * `features` - the records to route through the tree
+    ///
+    /// # Returns
+    /// the decision path of each record
+    pub async fn decision_path(&self, features: &dyn Features) -> Vec<DecisionPath> {
+        let path_futures = (0..features.n_rows())
+            .map(|row| async move {
+                let mut steps = vec![];
+                let leaf = self.navigate_tree(features, row, Some(&mut steps));
+                DecisionPath { steps, leaf }
+            })
+            .collect::<Vec<_>>();
+        join_all(path_futures).await
+    }
 
-        // find the root node of the tree
-        let root_best_split =
-            pick_best_split(&all_splits, features, targets, &self.loss_fn, None).await;
-        if let Some(ref root_split) = root_best_split {
-            let root_node_data = create_node_data(&root_split).await;
-            let root_node = self.nodes.add_new_node(root_node_data, &parent_node, None);
-            current_depth += 1;
+    /// The class with the lowest expected cost under the class distribution of a node
+    fn min_cost_class(
+        &self,
+        node_id: NodeId,
+        cost_matrix: &CostMatrix<L>,
+    ) -> Result<L, CostMatrixError<L>> {
+        let class_counts = self.nodes.nodes[node_id.index]
+            .data
+            .encoded_class_counts
+            .iter()
+            .map(|(class, count)| (self.label_encoder.decode(*class).clone(), *count))
+            .collect();
+        cost_matrix.min_cost_class(&class_counts)
+    }
 
-            match root_node_data.node_type {
-                NodeType::Leaf => (),
-                NodeType::Branch => self.build_child_nodes(
-                    Some(&root_split.node_1_indices),
-                    Some(&root_split.node_2_indices),
-                    &all_splits,
-                    features,
-                    targets,
-                    &self.loss_fn.clone(),
-                    &Some(root_node),
-                ).await,
+    /// Route a record from the root of the tree to a leaf, recording each decision in `steps`
+    ///
+    /// Only the values of the columns split on are read so sparse records aren't densified.
+    fn navigate_tree(
+        &self,
+        features: &dyn Features,
+        row: usize,
+        mut steps: Option<&mut Vec<DecisionStep>>,
+    ) -> NodeId {
+        let mut current_node_id = self.nodes.root().expect("The tree has not been fit");
+        loop {
+            let current_node = &self.nodes.nodes[current_node_id.index];
+            let record_value = match current_node.data.node_type {
+                NodeType::Leaf => return current_node_id,
+                NodeType::Branch => features.value(row, current_node.data.column),
+            };
+            let child_type = current_node.data.value_direction(record_value);
+            let child_node_id = match child_type {
+                ChildType::First => current_node.first_child,
+                ChildType::Second => current_node.second_child,
+            };
+            let Some(child_node_id) = child_node_id else {
+                return current_node_id;
+            };
+            if let Some(steps) = steps.as_mut() {
+                steps.push(DecisionStep {
+                    node: current_node_id,
+                    column: current_node.data.column,
+                    value: current_node.data.value,
+                    categories: current_node.data.categories.clone(),
+                    record_value,
+                    direction: child_type,
+                });
             }
-        } else {
-            panic!("Couldn't find a good split when searching, this occurred when trying to build the root node of the tress")
+            current_node_id = child_node_id;
         }
     }
+}
+
+/// A single comparison made while routing a record through the tree
+///
+/// # Arguments
+/// * `node` - the branch node making the comparison
+/// * `column` - the column compared
+/// * `value` - the value the branch splits at
+/// * `categories` - for splits on categorical columns, the categories sent to the first child
+/// * `record_value` - the record's value in `column`
+/// * `direction` - the child the record was sent to, records greater than `value` go to the first.
+///   On categorical splits records with a category in `categories` go to the first, those with
+///   another category seen in training go to the second and those with any other category follow
+///   the node's default direction, as do records missing `record_value`
+#[derive(Clone, Debug)]
+pub struct DecisionStep {
+    pub node: NodeId,
+    pub column: usize,
+    pub value: f64,
+    pub categories: Option<CategorySet>,
+    pub record_value: f64,
+    pub direction: ChildType,
+}
 
-    async fn predict(&self, features: &[Vec<f64>]) -> Vec<isize> {
-        let root_node = &self.nodes.nodes[0];
-        let pred_futures = features
+/// The route a record takes from the root of the tree to a leaf
+///
+/// # Arguments
+/// * `steps` - the comparisons made at each branch, starting at the root
+/// * `leaf` - the leaf the record lands in
+#[derive(Clone, Debug)]
+pub struct DecisionPath {
+    pub steps: Vec<DecisionStep>,
+    pub leaf: NodeId,
+}
+
+impl DecisionPath {
+    /// Every node visited, starting at the root and ending with the leaf
+    pub fn nodes(&self) -> Vec<NodeId> {
+        self.steps
             .iter()
-            .map(|record |async {
-                self.navigate_tree(record, root_node).await
+            .map(|step| step.node)
+            .chain(std::iter::once(self.leaf))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl<L: Label> Model<L> for ClassificationTree<L> {
+    async fn fit(&mut self, features: &dyn Features, targets: &[L]) {
+        self.fit_weighted(features, targets, &vec![1.0; targets.len()]).await
+    }
+
+    async fn predict(&self, features: &dyn Features) -> Vec<L> {
+        let pred_futures = (0..features.n_rows())
+            .map(|row |async move {
+                let leaf = self.navigate_tree(features, row, None);
+                let prediction = self.nodes.nodes[leaf.index].data.encoded_prediction;
+                self.label_encoder.decode(prediction).clone()
             }
             )
             .collect::<Vec<_>>();
-        let preds = join_all(pred_futures).await;
-        preds
+        join_all(pred_futures).await
     }
 
 
-    async fn predict_proba(&self, features: &[Vec<f64>]) -> Vec<(f64, f64)>{
-        unimplemented!()
+    /// The weighted proportion of the training records of each class in the leaf each record
+    /// lands in, uniform for leaves no weight reached
+    async fn predict_proba(&self, features: &dyn Features) -> Vec<Vec<f64>> {
+        let n_classes = self.label_encoder.classes().len();
+        let proba_futures = (0..features.n_rows())
+            .map(|row| async move {
+                let leaf = self.navigate_tree(features, row, None);
+                let class_counts = &self.nodes.nodes[leaf.index].data.encoded_class_counts;
+                let total = class_counts.values().sum::<f64>();
+                // leaves no weight reached have no class distribution to go on
+                if total == 0.0 {
+                    return vec![1.0 / n_classes as f64; n_classes];
+                }
+                let mut probabilities = vec![0.0; n_classes];
+                for (class, count) in class_counts {
+                    probabilities[*class as usize] = count / total;
+                }
+                probabilities
+            })
+            .collect::<Vec<_>>();
+        join_all(proba_futures).await
     }
 
-    async fn score(&self, features: &[Vec<f64>], targets: &[isize], metric: Metric) -> f64{
-        let preds = &self.predict(features).await;
-
-        let metirc_fn = match metric {
-            Metric::Accuracy => accuracy,
-            _ => panic!()
-        };
-
-        let metric_result = metirc_fn(preds, targets);
-        metric_result
+    fn classes(&self) -> &[L] {
+        self.label_encoder.classes()
     }
 }
@@ -0,0 +1,206 @@
+use crate::preprocessing::LabelEncoder;
+use crate::Label;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+/// The cost of each kind of misclassification
+///
+/// # Arguments
+/// * `classes` - the classes the matrix covers
+/// * `costs` - `costs[i][j]` is the cost of predicting `classes[j]` for a record of class
+///   `classes[i]`
+#[derive(Clone, Debug)]
+pub struct CostMatrix<L> {
+    classes: Vec<L>,
+    costs: Vec<Vec<f64>>,
+}
+
+/// An invalid cost matrix, or a lookup of a class it doesn't cover
+#[derive(Clone, Debug, PartialEq)]
+pub enum CostMatrixError<L> {
+    /// The costs aren't N by N for N classes
+    Shape { n_classes: usize },
+    /// A class is listed more than once
+    DuplicateClass(L),
+    /// A cost is negative or not a number
+    InvalidCost(f64),
+    /// A class isn't covered by the matrix
+    MissingClass(L),
+}
+
+impl<L: Label> Display for CostMatrixError<L> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CostMatrixError::Shape { n_classes } => {
+                write!(
+                    f,
+                    "A cost matrix must be {n_classes} by {n_classes} for {n_classes} classes"
+                )
+            }
+            CostMatrixError::DuplicateClass(class) => {
+                write!(f, "Class {class:?} is listed more than once")
+            }
+            CostMatrixError::InvalidCost(cost) => {
+                write!(f, "Costs must be non-negative numbers, found {cost}")
+            }
+            CostMatrixError::MissingClass(class) => {
+                write!(f, "Class {class:?} is missing from the cost matrix")
+            }
+        }
+    }
+}
+
+impl<L: Label> std::error::Error for CostMatrixError<L> {}
+
+impl<L: Label> CostMatrix<L> {
+    /// # Errors
+    /// if `costs` isn't N by N for the N `classes`, a class is repeated or a cost is negative or
+    /// NaN
+    pub fn new(classes: Vec<L>, costs: Vec<Vec<f64>>) -> Result<Self, CostMatrixError<L>> {
+        if costs.len() != classes.len() || costs.iter().any(|row| row.len() != classes.len()) {
+            return Err(CostMatrixError::Shape {
+                n_classes: classes.len(),
+            });
+        }
+        for (i, class) in classes.iter().enumerate() {
+            if classes[..i].contains(class) {
+                return Err(CostMatrixError::DuplicateClass(class.clone()));
+            }
+        }
+        if let Some(cost) = costs
+            .iter()
+            .flatten()
+            .find(|cost| cost.is_nan() || **cost < 0.0)
+        {
+            return Err(CostMatrixError::InvalidCost(*cost));
+        }
+        Ok(Self { classes, costs })
+    }
+
+    pub fn classes(&self) -> &[L] {
+        &self.classes
+    }
+
+    /// The cost of predicting `predicted` for a record of class `actual`
+    ///
+    /// # Errors
+    /// if either class isn't covered by the matrix
+    pub fn cost(&self, actual: &L, predicted: &L) -> Result<f64, CostMatrixError<L>> {
+        Ok(self.costs[self.class_index(actual)?][self.class_index(predicted)?])
+    }
+
+    /// The class with the lowest expected cost given the distribution of classes in a node,
+    /// ties are broken in favour of the class listed first
+    ///
+    /// # Arguments
+    /// * `class_counts` - the total weight of the records of each class in the node
+    ///
+    /// # Errors
+    /// if a class in `class_counts` isn't covered by the matrix
+    pub fn min_cost_class(&self, class_counts: &BTreeMap<L, f64>) -> Result<L, CostMatrixError<L>> {
+        let class_counts = class_counts
+            .iter()
+            .map(|(class, count)| Ok((self.class_index(class)?, *count)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let best_class = min_cost_index(&self.costs, class_counts);
+        Ok(self.classes[best_class].clone())
+    }
+
+    /// The costs indexed by the encodings of a label encoder's classes rather than the order
+    /// classes are listed in
+    ///
+    /// # Errors
+    /// if a class known to the encoder isn't covered by the matrix
+    pub(crate) fn encode(
+        &self,
+        label_encoder: &LabelEncoder<L>,
+    ) -> Result<Vec<Vec<f64>>, CostMatrixError<L>> {
+        let indices = label_encoder
+            .classes()
+            .iter()
+            .map(|class| self.class_index(class))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(indices
+            .iter()
+            .map(|actual| {
+                indices
+                    .iter()
+                    .map(|predicted| self.costs[*actual][*predicted])
+                    .collect()
+            })
+            .collect())
+    }
+
+    fn class_index(&self, class: &L) -> Result<usize, CostMatrixError<L>> {
+        self.classes
+            .iter()
+            .position(|c| c == class)
+            .ok_or_else(|| CostMatrixError::MissingClass(class.clone()))
+    }
+}
+
+/// The index of the class with the lowest expected cost, ties are broken in favour of the lowest
+/// index
+///
+/// # Arguments
+/// * `costs` - `costs[i][j]` is the cost of predicting class `j` for a record of class `i`
+/// * `class_counts` - the index and total weight of each class present
+pub(crate) fn min_cost_index(
+    costs: &[Vec<f64>],
+    class_counts: impl IntoIterator<Item = (usize, f64)> + Clone,
+) -> usize {
+    let expected_cost = |predicted: usize| -> f64 {
+        class_counts
+            .clone()
+            .into_iter()
+            .map(|(actual, count)| count * costs[actual][predicted])
+            .sum()
+    };
+    let mut best_class = 0;
+    let mut min_cost = f64::INFINITY;
+    for predicted in 0..costs.len() {
+        let cost = expected_cost(predicted);
+        if cost < min_cost {
+            min_cost = cost;
+            best_class = predicted;
+        }
+    }
+    best_class
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_cost_matrix() {
+        assert_eq!(
+            CostMatrix::new(vec![0, 1], vec![vec![0.0, 1.0]]).unwrap_err(),
+            CostMatrixError::Shape { n_classes: 2 }
+        );
+        assert_eq!(
+            CostMatrix::new(vec![0, 0], vec![vec![0.0, 1.0], vec![1.0, 0.0]]).unwrap_err(),
+            CostMatrixError::DuplicateClass(0)
+        );
+        assert_eq!(
+            CostMatrix::new(vec![0, 1], vec![vec![0.0, -1.0], vec![1.0, 0.0]]).unwrap_err(),
+            CostMatrixError::InvalidCost(-1.0)
+        );
+
+        let cost_matrix =
+            CostMatrix::new(vec![0, 1], vec![vec![0.0, 1.0], vec![5.0, 0.0]]).unwrap();
+        assert_eq!(cost_matrix.cost(&1, &0), Ok(5.0));
+        assert_eq!(
+            cost_matrix.cost(&2, &0),
+            Err(CostMatrixError::MissingClass(2))
+        );
+        assert_eq!(
+            cost_matrix.min_cost_class(&BTreeMap::from([(0, 4.0), (1, 1.0)])),
+            Ok(1)
+        );
+        assert_eq!(
+            cost_matrix.min_cost_class(&BTreeMap::from([(2, 1.0)])),
+            Err(CostMatrixError::MissingClass(2))
+        );
+    }
+}
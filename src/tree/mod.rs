@@ -1,5 +1,15 @@
 mod classification_tree;
+mod cost_matrix;
+mod pruning;
 mod tree_core;
 mod tree_utils;
 
-pub use classification_tree::{ClassificationTree, ClassificationTreeBuilder};
+pub use classification_tree::{
+    ClassWeight, ClassificationTree, ClassificationTreeBuilder, DecisionPath, DecisionStep,
+};
+pub use cost_matrix::{CostMatrix, CostMatrixError};
+pub use pruning::PruningReport;
+pub use tree_core::{
+    Arena, BreadthFirstIter, CategorySet, ChildType, DepthFirstIter, Node, NodeData, NodeId,
+    NodeType,
+};
@@ -0,0 +1,103 @@
+use crate::data::Features;
+use crate::metrics::{Metric, Scorer};
+use crate::tree::tree_core::{Arena, NodeId, NodeType};
+use crate::tree::ClassificationTree;
+use crate::{Label, Model};
+
+/// Summary of a pruning pass over a tree
+///
+/// # Arguments
+/// * `collapsed_nodes` - branch nodes that were turned into leaves
+/// * `removed_nodes` - nodes removed from the tree, these stay in the arena until it is compacted
+/// * `score_before` - validation score of the tree before pruning
+/// * `score_after` - validation score of the tree after pruning
+#[derive(Clone, Debug, Default)]
+pub struct PruningReport {
+    pub collapsed_nodes: Vec<NodeId>,
+    pub removed_nodes: Vec<NodeId>,
+    pub score_before: f64,
+    pub score_after: f64,
+}
+
+impl<L: Label> ClassificationTree<L> {
+    /// Reduced-error pruning against a holdout set, scored with accuracy
+    ///
+    /// # Arguments
+    /// * `features` - validation features
+    /// * `targets` - validation targets
+    ///
+    /// # Returns
+    /// a report of the nodes that were pruned
+    pub async fn prune_with_validation(
+        &mut self,
+        features: &dyn Features,
+        targets: &[L],
+    ) -> PruningReport {
+        self.prune_with_validation_by(features, targets, Metric::Accuracy)
+            .await
+    }
+
+    /// Reduced-error pruning against a holdout set
+    ///
+    /// Works bottom-up through the tree, replacing each branch with a leaf predicting the majority
+    /// class of the training records that reached it whenever doing so does not make the score
+    /// of the tree on the validation set worse.
+    ///
+    /// # Arguments
+    /// * `features` - validation features
+    /// * `targets` - validation targets
+    /// * `scorer` - the metric or scorer used to score the tree on the validation set
+    ///
+    /// # Returns
+    /// a report of the nodes that were pruned
+    pub async fn prune_with_validation_by<S: Scorer<L>>(
+        &mut self,
+        features: &dyn Features,
+        targets: &[L],
+        scorer: S,
+    ) -> PruningReport {
+        let score_before = self.score(features, targets, &scorer).await;
+        let mut report = PruningReport {
+            score_before,
+            score_after: score_before,
+            ..Default::default()
+        };
+
+        for node_id in branches_post_order(&self.nodes) {
+            let node = &mut self.nodes.nodes[node_id.index];
+            let first_child = node.first_child.take();
+            let second_child = node.second_child.take();
+            node.data.node_type = NodeType::Leaf;
+
+            let score = self.score(features, targets, &scorer).await;
+            if scorer.at_least_as_good(score, report.score_after) {
+                report.score_after = score;
+                report.collapsed_nodes.push(node_id);
+                for child in first_child.iter().chain(second_child.iter()) {
+                    report.removed_nodes.extend(self.nodes.remove_subtree(*child));
+                }
+            } else {
+                let node = &mut self.nodes.nodes[node_id.index];
+                node.first_child = first_child;
+                node.second_child = second_child;
+                node.data.node_type = NodeType::Branch;
+            }
+        }
+
+        report
+    }
+}
+
+/// Branch nodes reachable from the root, with every node visited after its children
+fn branches_post_order(arena: &Arena) -> Vec<NodeId> {
+    let Some(root) = arena.root() else {
+        return vec![];
+    };
+    let mut branches = arena
+        .depth_first(root)
+        .filter(|node_id| arena.nodes[node_id.index].data.node_type == NodeType::Branch)
+        .collect::<Vec<_>>();
+    // depth first visits parents before their children so reversing it gives a valid post-order
+    branches.reverse();
+    branches
+}
@@ -1,6 +1,10 @@
-#[derive(Clone, Debug)]
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+#[derive(Clone, Debug, Default)]
 pub struct Arena {
     pub nodes: Vec<Node>,
+    root: Option<NodeId>,
+    removed: BTreeSet<NodeId>,
 }
 
 impl Arena {
@@ -20,13 +24,16 @@ impl Arena {
             second_child: None,
             data,
         });
+        if parent.is_none() && self.root.is_none() {
+            self.root = Some(new_node_id);
+        }
         if let Some(parent_node) = parent {
             match child_type {
                 Some(ChildType::First) => {
-                    self.nodes[parent_node.index].first_child = Some(new_node_id.clone())
+                    self.nodes[parent_node.index].first_child = Some(new_node_id)
                 }
                 Some(ChildType::Second) => {
-                    self.nodes[parent_node.index].second_child = Some(new_node_id.clone())
+                    self.nodes[parent_node.index].second_child = Some(new_node_id)
                 }
                 _ => (),
             }
@@ -34,6 +41,208 @@ impl Arena {
 
         new_node_id
     }
+
+    /// The root of the tree, if it has one
+    ///
+    /// The first node added without a parent becomes the root.
+    pub fn root(&self) -> Option<NodeId> {
+        self.root
+    }
+
+    /// Whether a node has been removed with [`Arena::remove_subtree`] and not yet compacted away
+    pub fn is_removed(&self, node_id: NodeId) -> bool {
+        self.removed.contains(&node_id)
+    }
+
+    /// Detach a node and all of its descendants from the tree
+    ///
+    /// The removed nodes stay in the arena, so existing `NodeId`s remain valid, until
+    /// [`Arena::compact`] is called.
+    ///
+    /// # Returns
+    /// the ids of the removed nodes
+    pub fn remove_subtree(&mut self, node_id: NodeId) -> Vec<NodeId> {
+        self.detach(node_id);
+        if self.root == Some(node_id) {
+            self.root = None;
+        }
+        let removed = self.depth_first(node_id).collect::<Vec<_>>();
+        self.removed.extend(removed.iter().copied());
+        removed
+    }
+
+    /// Move a node, along with its descendants, to be a child of `new_parent`
+    ///
+    /// Any subtree `new_parent` already held in that position is removed, as with
+    /// [`Arena::remove_subtree`].
+    ///
+    /// # Returns
+    /// the ids of the removed nodes
+    ///
+    /// # Panics
+    /// if `new_parent` is `node_id` or one of its descendants
+    pub fn reparent(
+        &mut self,
+        node_id: NodeId,
+        new_parent: NodeId,
+        child_type: ChildType,
+    ) -> Vec<NodeId> {
+        assert!(
+            !self.path_to_root(new_parent).contains(&node_id),
+            "Cannot move a node beneath itself"
+        );
+        let displaced = match child_type {
+            ChildType::First => self.nodes[new_parent.index].first_child,
+            ChildType::Second => self.nodes[new_parent.index].second_child,
+        };
+        let removed = match displaced {
+            Some(displaced_id) if displaced_id != node_id => self.remove_subtree(displaced_id),
+            _ => vec![],
+        };
+        self.detach(node_id);
+        if self.root == Some(node_id) {
+            self.root = None;
+        }
+        let parent = &mut self.nodes[new_parent.index];
+        match child_type {
+            ChildType::First => parent.first_child = Some(node_id),
+            ChildType::Second => parent.second_child = Some(node_id),
+        }
+        self.nodes[node_id.index].parent = Some(new_parent);
+        removed
+    }
+
+    /// Drop removed nodes from the arena, re-indexing the nodes that remain
+    ///
+    /// The relative order of the remaining nodes is preserved.
+    ///
+    /// # Returns
+    /// the new id of each old node, indexed by the old id, `None` for nodes that were dropped
+    pub fn compact(&mut self) -> Vec<Option<NodeId>> {
+        let mut mapping = Vec::with_capacity(self.nodes.len());
+        let mut next_index = 0;
+        for index in 0..self.nodes.len() {
+            if self.removed.contains(&NodeId { index }) {
+                mapping.push(None);
+            } else {
+                mapping.push(Some(NodeId { index: next_index }));
+                next_index += 1;
+            }
+        }
+
+        let remap = |node_id: Option<NodeId>| node_id.and_then(|id| mapping[id.index]);
+        let nodes = std::mem::take(&mut self.nodes);
+        self.nodes = nodes
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| mapping[*index].is_some())
+            .map(|(_, node)| Node {
+                parent: remap(node.parent),
+                first_child: remap(node.first_child),
+                second_child: remap(node.second_child),
+                data: node.data,
+            })
+            .collect();
+        self.root = remap(self.root);
+        self.removed.clear();
+        mapping
+    }
+
+    /// Iterate over a subtree depth first, visiting each node before its children and the first
+    /// child before the second
+    pub fn depth_first(&self, start: NodeId) -> DepthFirstIter<'_> {
+        DepthFirstIter {
+            arena: self,
+            stack: vec![start],
+        }
+    }
+
+    /// Iterate over a subtree breadth first, visiting nodes level by level
+    pub fn breadth_first(&self, start: NodeId) -> BreadthFirstIter<'_> {
+        BreadthFirstIter {
+            arena: self,
+            queue: VecDeque::from([start]),
+        }
+    }
+
+    /// The number of edges between a node and the root of its tree
+    pub fn depth(&self, node_id: NodeId) -> usize {
+        self.path_to_root(node_id).len() - 1
+    }
+
+    /// The leaves of the tree, in depth first order
+    pub fn leaves(&self) -> Vec<NodeId> {
+        self.root()
+            .map(|root| {
+                self.depth_first(root)
+                    .filter(|node_id| {
+                        let node = &self.nodes[node_id.index];
+                        node.first_child.is_none() && node.second_child.is_none()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The nodes between a node and the root of its tree, starting with the node itself and
+    /// ending with the root
+    pub fn path_to_root(&self, node_id: NodeId) -> Vec<NodeId> {
+        let mut path = vec![node_id];
+        let mut current = node_id;
+        while let Some(parent) = self.nodes[current.index].parent {
+            path.push(parent);
+            current = parent;
+        }
+        path
+    }
+
+    /// Unlink a node from its parent
+    fn detach(&mut self, node_id: NodeId) {
+        if let Some(parent_id) = self.nodes[node_id.index].parent.take() {
+            let parent = &mut self.nodes[parent_id.index];
+            if parent.first_child == Some(node_id) {
+                parent.first_child = None;
+            }
+            if parent.second_child == Some(node_id) {
+                parent.second_child = None;
+            }
+        }
+    }
+}
+
+/// Depth first (pre-order) iterator over the nodes of an [`Arena`]
+pub struct DepthFirstIter<'a> {
+    arena: &'a Arena,
+    stack: Vec<NodeId>,
+}
+
+impl Iterator for DepthFirstIter<'_> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.stack.pop()?;
+        let node = &self.arena.nodes[current.index];
+        // push the second child first so the first child is visited first
+        self.stack.extend(node.second_child.iter().chain(node.first_child.iter()));
+        Some(current)
+    }
+}
+
+/// Breadth first iterator over the nodes of an [`Arena`]
+pub struct BreadthFirstIter<'a> {
+    arena: &'a Arena,
+    queue: VecDeque<NodeId>,
+}
+
+impl Iterator for BreadthFirstIter<'_> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.queue.pop_front()?;
+        let node = &self.arena.nodes[current.index];
+        self.queue.extend(node.first_child.iter().chain(node.second_child.iter()));
+        Some(current)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -44,41 +253,213 @@ pub struct Node {
     pub data: NodeData,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct NodeId {
     pub index: usize,
 }
 
-///
-#[derive(Clone, Debug, Copy)]
+/// Whether a node splits the data further or predicts
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
 pub enum NodeType {
     Branch,
     Leaf,
 }
 
-///
+/// Data held by each node of the tree
 ///
 /// # Arguments
-/// * `node_type`
-/// * `column`
-/// * `value`
-/// * `loss`
-#[derive(Clone, Copy, Debug)]
+/// * `node_type` - whether the node is a branch or a leaf
+/// * `column` - the column the node splits on
+/// * `value` - the value the node splits at, records greater than this go to the first child
+/// * `categories` - for splits on categorical columns, the categories sent to the first child in
+///   place of `value`
+/// * `second_categories` - for splits on categorical columns, the categories seen in training that
+///   are sent to the second child, any other category follows `default_direction`
+/// * `loss` - the loss of the split, the weighted impurity of the node's children
+/// * `impurity` - the impurity of the training records that reached the node
+/// * `n_samples` - the number of training records that reached the node
+/// * `weighted_n_samples` - the total weight of the training records that reached the node
+/// * `default_direction` - the child records missing a value in `column`, or with a category not
+///   seen in training, are sent to
+/// * `encoded_prediction` - the class the node predicts, the most common class of the training
+///   records that reached the node or the one with the lowest expected cost if the tree has a
+///   cost matrix. This is the class's label encoder index, decode it with
+///   [`LabelEncoder::decode`](crate::preprocessing::LabelEncoder::decode)
+/// * `encoded_class_counts` - the total weight of the training records of each class that reached
+///   the node, the number of records when fit without weights, keyed by label encoder index
+#[derive(Clone, Debug)]
 pub struct NodeData {
     pub node_type: NodeType,
     pub column: usize,
     pub value: f64,
+    pub categories: Option<CategorySet>,
+    pub second_categories: Option<CategorySet>,
     pub loss: f64,
-    pub majority_class: isize
+    pub impurity: f64,
+    pub n_samples: usize,
+    pub weighted_n_samples: f64,
+    pub default_direction: ChildType,
+    pub encoded_prediction: isize,
+    pub encoded_class_counts: BTreeMap<isize, f64>,
+}
+
+impl NodeData {
+    /// The child a record is sent to, records missing a value in the split column follow the
+    /// node's default direction
+    pub fn direction(&self, record: &[f64]) -> ChildType {
+        self.value_direction(record[self.column])
+    }
+
+    /// The child a record with `value` in the split column is sent to
+    pub fn value_direction(&self, value: f64) -> ChildType {
+        // a category seen by neither child is no more use to the split than a missing value
+        let seen = match (&self.categories, &self.second_categories) {
+            (Some(categories), Some(second_categories)) => {
+                as_category(value).is_some_and(|category| {
+                    categories.contains(category) || second_categories.contains(category)
+                })
+            }
+            _ => true,
+        };
+        match split_direction(value, self.value, self.categories.as_ref()) {
+            Some(direction) if seen => direction,
+            _ => self.default_direction,
+        }
+    }
+}
+
+/// The child a value is sent to by a split, `None` if the value is missing or, for splits on
+/// categorical columns, isn't a category
+pub(crate) fn split_direction(
+    value: f64,
+    threshold: f64,
+    categories: Option<&CategorySet>,
+) -> Option<ChildType> {
+    let goes_to_first_child = match categories {
+        Some(categories) => categories.contains(as_category(value)?),
+        None if value.is_nan() => return None,
+        None => value > threshold,
+    };
+    Some(match goes_to_first_child {
+        true => ChildType::First,
+        false => ChildType::Second,
+    })
+}
+
+/// The category a value of a categorical column represents, categories are non-negative integers
+pub(crate) fn as_category(value: f64) -> Option<usize> {
+    (value >= 0.0 && value.fract() == 0.0).then_some(value as usize)
+}
+
+/// A set of categories stored sorted, so a set holding a large category code is no bigger than
+/// one holding a small one
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CategorySet {
+    categories: Vec<usize>,
 }
 
-impl Default for Arena {
-    fn default() -> Self {
-        Arena { nodes: vec![] }
+impl CategorySet {
+    pub fn insert(&mut self, category: usize) {
+        if let Err(position) = self.categories.binary_search(&category) {
+            self.categories.insert(position, category);
+        }
+    }
+
+    pub fn contains(&self, category: usize) -> bool {
+        self.categories.binary_search(&category).is_ok()
+    }
+
+    /// The categories in the set in ascending order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.categories.iter().copied()
     }
 }
 
+impl FromIterator<usize> for CategorySet {
+    fn from_iter<T: IntoIterator<Item = usize>>(iter: T) -> Self {
+        let mut categories = iter.into_iter().collect::<Vec<_>>();
+        categories.sort_unstable();
+        categories.dedup();
+        CategorySet { categories }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ChildType {
     First,
     Second,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf_data() -> NodeData {
+        NodeData {
+            node_type: NodeType::Leaf,
+            column: 0,
+            value: 0.0,
+            categories: None,
+            second_categories: None,
+            loss: 0.0,
+            impurity: 0.0,
+            n_samples: 0,
+            weighted_n_samples: 0.0,
+            default_direction: ChildType::Second,
+            encoded_prediction: 0,
+            encoded_class_counts: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_remove_and_compact() {
+        // 0 -> (1 -> (3, 4), 2)
+        let mut arena = Arena::default();
+        let root = arena.add_new_node(leaf_data(), &None, None);
+        let a = arena.add_new_node(leaf_data(), &Some(root), Some(ChildType::First));
+        let b = arena.add_new_node(leaf_data(), &Some(root), Some(ChildType::Second));
+        let c = arena.add_new_node(leaf_data(), &Some(a), Some(ChildType::First));
+        let d = arena.add_new_node(leaf_data(), &Some(a), Some(ChildType::Second));
+
+        assert_eq!(arena.depth_first(root).collect::<Vec<_>>(), vec![root, a, c, d, b]);
+        assert_eq!(arena.breadth_first(root).collect::<Vec<_>>(), vec![root, a, b, c, d]);
+        assert_eq!(arena.leaves(), vec![c, d, b]);
+        assert_eq!(arena.path_to_root(d), vec![d, a, root]);
+        assert_eq!(arena.depth(d), 2);
+
+        assert_eq!(arena.remove_subtree(a), vec![a, c, d]);
+        assert!(arena.is_removed(c));
+        assert_eq!(arena.leaves(), vec![b]);
+
+        let mapping = arena.compact();
+        assert_eq!(mapping, vec![Some(root), None, Some(NodeId { index: 1 }), None, None]);
+        assert_eq!(arena.nodes.len(), 2);
+        assert_eq!(arena.nodes[0].second_child, Some(NodeId { index: 1 }));
+        assert_eq!(arena.nodes[1].parent, Some(root));
+    }
+
+    #[test]
+    fn test_reparent_and_remove_root() {
+        // 0 -> (1 -> (3, 4), 2)
+        let mut arena = Arena::default();
+        let root = arena.add_new_node(leaf_data(), &None, None);
+        let a = arena.add_new_node(leaf_data(), &Some(root), Some(ChildType::First));
+        let b = arena.add_new_node(leaf_data(), &Some(root), Some(ChildType::Second));
+        let c = arena.add_new_node(leaf_data(), &Some(a), Some(ChildType::First));
+        let d = arena.add_new_node(leaf_data(), &Some(a), Some(ChildType::Second));
+
+        // moving 3 into the place of 2 removes 2
+        assert_eq!(arena.reparent(c, root, ChildType::Second), vec![b]);
+        assert!(arena.is_removed(b));
+        assert_eq!(arena.nodes[a.index].first_child, None);
+        assert_eq!(arena.depth_first(root).collect::<Vec<_>>(), vec![root, a, d, c]);
+
+        // a new root can be grown once the old one is removed and compacted away
+        arena.remove_subtree(root);
+        assert_eq!(arena.root(), None);
+        arena.compact();
+        assert!(arena.nodes.is_empty());
+        let new_root = arena.add_new_node(leaf_data(), &None, None);
+        assert_eq!(arena.root(), Some(new_root));
+    }
+}
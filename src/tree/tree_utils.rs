@@ -1,168 +1,727 @@
-use std::collections::HashMap;
-use crate::loss_functions::{weighted_gini_impurity, LossFunction};
-use crate::tree::tree_core::{NodeData, NodeType};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use crate::data::{CscMatrix, Dataset, Features};
+use crate::loss_functions::{
+    gini_impurity_from_weights, weighted_gini_impurity_from_weights, LossFunction,
+};
+use crate::tree::cost_matrix::min_cost_index;
+use crate::tree::tree_core::{
+    as_category, split_direction, CategorySet, ChildType, NodeData, NodeType,
+};
 
-/// Given a vector of tree splits pick one that minimises loss
+/// The features a tree is fit on, sparse features are kept sparse
+pub(crate) enum TrainingFeatures<'a> {
+    Dense(Cow<'a, Dataset>),
+    Sparse(Cow<'a, CscMatrix>),
+}
+
+impl<'a> TrainingFeatures<'a> {
+    pub(crate) fn new(features: &'a dyn Features) -> Self {
+        match features.to_csc() {
+            Some(csc) => TrainingFeatures::Sparse(csc),
+            None => TrainingFeatures::Dense(features.to_dataset()),
+        }
+    }
+
+    pub(crate) fn n_columns(&self) -> usize {
+        match self {
+            TrainingFeatures::Dense(dataset) => dataset.n_columns(),
+            TrainingFeatures::Sparse(csc) => csc.shape().1,
+        }
+    }
+}
+
+/// The records a tree is fit on
+pub(crate) struct TrainingData<'a> {
+    pub(crate) features: &'a TrainingFeatures<'a>,
+    pub(crate) targets: &'a [isize],
+    pub(crate) sample_weights: &'a [f64],
+    /// Misclassification costs indexed by encoded class, see [`cost_gini_impurity`]
+    pub(crate) costs: Option<&'a [Vec<f64>]>,
+}
+
+impl TrainingData<'_> {
+    /// Indices of the records in a node, all records if there's no filter
+    fn records<'b>(&self, filter: Option<&'b [usize]>) -> Box<dyn Iterator<Item = usize> + 'b> {
+        match filter {
+            Some(index_filter) => Box::new(index_filter.iter().copied()),
+            None => Box::new(0..self.targets.len()),
+        }
+    }
+
+    /// The value of a column for each record in a node, in the order of the node's records
+    ///
+    /// Sparse columns are merged against the node's records, which are in ascending order, rather
+    /// than densified. Records without a stored value are `None`, their value is zero.
+    fn node_values<'b>(
+        &'b self,
+        column: usize,
+        filter: Option<&'b [usize]>,
+    ) -> Box<dyn Iterator<Item = (usize, Option<f64>)> + 'b> {
+        match self.features {
+            TrainingFeatures::Dense(dataset) => {
+                let values = dataset.column(column);
+                Box::new(self.records(filter).map(move |i| (i, Some(values[i]))))
+            }
+            TrainingFeatures::Sparse(csc) => {
+                let (rows, values) = csc.column_entries(column);
+                let mut entries = rows.iter().zip(values).peekable();
+                Box::new(self.records(filter).map(move |i| {
+                    while entries.next_if(|(row, _)| **row < i).is_some() {}
+                    (i, entries.next_if(|(row, _)| **row == i).map(|(_, value)| *value))
+                }))
+            }
+        }
+    }
+
+    /// The stored values of a sparse column for the records in a node, every value of a dense
+    /// column
+    ///
+    /// # Returns
+    /// the index and value of each record with a stored value
+    fn stored_node_values<'b>(
+        &'b self,
+        column: usize,
+        filter: Option<&'b [usize]>,
+    ) -> Box<dyn Iterator<Item = (usize, f64)> + 'b> {
+        match self.features {
+            TrainingFeatures::Dense(dataset) => {
+                let values = dataset.column(column);
+                Box::new(self.records(filter).map(move |i| (i, values[i])))
+            }
+            TrainingFeatures::Sparse(csc) => {
+                let (rows, values) = csc.column_entries(column);
+                let in_node = move |row: &usize| {
+                    filter.is_none_or(|filter| filter.binary_search(row).is_ok())
+                };
+                Box::new(
+                    rows.iter()
+                        .zip(values)
+                        .filter(move |(row, _)| in_node(row))
+                        .map(|(row, value)| (*row, *value)),
+                )
+            }
+        }
+    }
+
+    /// The total weight of the records of each class in a set of records
+    fn class_counts(&self, indices: impl IntoIterator<Item = usize>) -> BTreeMap<isize, f64> {
+        let mut class_counts = BTreeMap::new();
+        for i in indices {
+            *class_counts.entry(self.targets[i]).or_insert(0.0) += self.sample_weights[i];
+        }
+        class_counts
+    }
+
+    /// The class a node predicts, the one with the lowest expected cost if there are
+    /// misclassification costs and the majority class otherwise
+    fn prediction(&self, class_counts: &BTreeMap<isize, f64>) -> Option<isize> {
+        match self.costs {
+            Some(_) if class_counts.is_empty() => None,
+            Some(costs) => {
+                let class_counts =
+                    class_counts.iter().map(|(class, count)| (*class as usize, *count));
+                Some(min_cost_index(costs, class_counts) as isize)
+            }
+            None => majority_class(class_counts),
+        }
+    }
+
+    /// The impurity of a node given its class counts
+    fn impurity(&self, loss_fn: &LossFunction, class_counts: &BTreeMap<isize, f64>) -> f64 {
+        match self.costs {
+            Some(costs) => cost_gini_impurity(costs, class_counts),
+            None => loss_functions(loss_fn).1(class_counts),
+        }
+    }
+
+    /// The loss of a split given the class counts of its two children
+    fn split_loss(
+        &self,
+        loss_fn: &LossFunction,
+        node_1_counts: &BTreeMap<isize, f64>,
+        node_2_counts: &BTreeMap<isize, f64>,
+    ) -> f64 {
+        let Some(costs) = self.costs else {
+            return loss_functions(loss_fn).0(node_1_counts, node_2_counts);
+        };
+        let node_1_len = node_1_counts.values().sum::<f64>();
+        let node_2_len = node_2_counts.values().sum::<f64>();
+        if node_1_len + node_2_len == 0.0 {
+            return 0.0;
+        }
+        (node_1_len * cost_gini_impurity(costs, node_1_counts)
+            + node_2_len * cost_gini_impurity(costs, node_2_counts))
+            / (node_1_len + node_2_len)
+    }
+}
+
+/// Gini impurity with each pair of classes weighted by the cost of confusing them,
+/// `sum C(i, j) p_i p_j` over `i != j` (Breiman et al., 1984)
+///
+/// Costs are scaled so the largest is one, keeping impurities within those of the unweighted gini
+/// impurity. Misclassification costs are used in place of the tree's loss function.
+fn cost_gini_impurity(costs: &[Vec<f64>], class_counts: &BTreeMap<isize, f64>) -> f64 {
+    let total = class_counts.values().sum::<f64>();
+    let max_cost = costs.iter().flatten().fold(0.0, |max: f64, cost| max.max(*cost));
+    if total == 0.0 || max_cost == 0.0 {
+        return 0.0;
+    }
+    let p = |count: &f64| count / total;
+    let mut impurity = 0.0;
+    for (actual, actual_count) in class_counts {
+        for (predicted, predicted_count) in class_counts {
+            if actual != predicted {
+                let cost = costs[*actual as usize][*predicted as usize];
+                impurity += cost * p(actual_count) * p(predicted_count);
+            }
+        }
+    }
+    impurity / max_cost
+}
+
+/// Given a vector of tree splits pick one that minimises loss, along with the best category
+/// subset splits of any categorical columns
+///
+/// Splits leaving either child with less than `min_leaf_weight` are ignored, if no split is
+/// allowed a split sending every record to the first child is returned which makes the node a
+/// leaf.
 ///
 /// O(n) where n os the number of splits
 pub(crate) async fn pick_best_split(
-    all_splits: &Vec<TreeSplit>,
-    features: &[Vec<f64>],
-    targets: &[isize],
+    all_splits: &[TreeSplit],
+    data: &TrainingData<'_>,
     loss_fn: &LossFunction,
     filter: Option<&[usize]>,
+    categorical_features: &[usize],
+    min_leaf_weight: f64,
 ) -> Option<SplitResult> {
+    let categorical_splits = categorical_features
+        .iter()
+        .flat_map(|column| generate_categorical_splits(data, *column, filter))
+        .collect::<Vec<_>>();
+
+    // splits of sparse features are searched per node rather than generated up front
+    let sparse_split = match data.features {
+        TrainingFeatures::Sparse(csc) => best_sparse_split(
+            csc,
+            data,
+            loss_fn,
+            filter,
+            categorical_features,
+            min_leaf_weight,
+        ),
+        TrainingFeatures::Dense(_) => None,
+    };
+
     let mut best_split: Option<SplitResult> = None;
     let mut min_loss = 1.0;
-    for split in all_splits {
-        let split_result = check_split(features, targets, &split, loss_fn, filter).await;
-        if split_result.loss < min_loss {
+    for split in all_splits
+        .iter()
+        .chain(&sparse_split)
+        .chain(&categorical_splits)
+    {
+        let split_result = check_split(data, split, loss_fn, filter).await;
+        if split_result.loss < min_loss
+            && split_result.node_1_weight >= min_leaf_weight
+            && split_result.node_2_weight >= min_leaf_weight
+        {
             min_loss = split_result.loss;
             best_split = Some(split_result);
         }
     }
-    best_split
+    best_split.or_else(|| leaf_split(data, loss_fn, filter))
 }
 
 #[derive(Clone)]
 pub(crate) struct SplitResult {
     pub(crate) value: f64,
+    pub(crate) categories: Option<CategorySet>,
+    pub(crate) second_categories: Option<CategorySet>,
     pub(crate) column: usize,
     pub(crate) loss: f64,
+    pub(crate) impurity: f64,
     pub(crate) node_1_indices: Vec<usize>,
     pub(crate) node_2_indices: Vec<usize>,
-    pub(crate) majority_class: isize
+    pub(crate) node_1_weight: f64,
+    pub(crate) node_2_weight: f64,
+    pub(crate) default_direction: ChildType,
+    pub(crate) encoded_prediction: isize,
+    pub(crate) encoded_class_counts: BTreeMap<isize, f64>,
+}
+
+/// The loss of a split given the class counts of its two children
+type SplitLossFn = fn(&BTreeMap<isize, f64>, &BTreeMap<isize, f64>) -> f64;
+/// The impurity of a node given its class counts
+type ImpurityFn = fn(&BTreeMap<isize, f64>) -> f64;
+
+/// Dynamic dispatch of loss function, will allow more ot be implemented later
+fn loss_functions(loss_fn: &LossFunction) -> (SplitLossFn, ImpurityFn) {
+    match loss_fn {
+        LossFunction::Gini => (weighted_gini_impurity_from_weights, gini_impurity_from_weights),
+    }
 }
 
 /// Given a tree split, check the loss of that split
 async fn check_split(
-    features: &[Vec<f64>],
-    targets: &[isize],
+    data: &TrainingData<'_>,
     split: &TreeSplit,
     loss_fn: &LossFunction,
     filter: Option<&[usize]>,
 ) -> SplitResult {
-    // dynamic dispatch of loss function, will allow more ot be implemented later
-    let loss_function: fn(Vec<&isize>, Vec<&isize>) -> f64 = match loss_fn {
-        LossFunction::Gini => weighted_gini_impurity,
-    };
-
-    // targets for each leaf
-    let mut node_1_targets = vec![];
-    let mut node_2_targets = vec![];
-
     let mut node_1_indices = vec![];
     let mut node_2_indices = vec![];
+    let mut missing_indices = vec![];
 
-    for (i, (record, record_target)) in features.iter().zip(targets).enumerate() {
-        if record[split.column] > split.value {
-            if let Some(index_filter) = filter {
-                if index_filter.contains(&i) {
-                    node_1_targets.push(record_target);
-                    node_1_indices.push(i)
-                }
-            } else {
-                node_1_targets.push(record_target);
-                node_1_indices.push(i)
-            }
-        } else {
-            if let Some(index_filter) = filter {
-                if index_filter.contains(&i) {
-                    node_2_targets.push(record_target);
-                    node_2_indices.push(i)
-                }
-            } else {
-                node_2_targets.push(record_target);
-                node_2_indices.push(i)
-            }
+    // records without a stored sparse value are all zero, so go the same way
+    let zero_direction = split_direction(0.0, split.value, split.categories.as_ref());
+    for (i, value) in data.node_values(split.column, filter) {
+        let direction = match value {
+            Some(value) => split_direction(value, split.value, split.categories.as_ref()),
+            None => zero_direction,
+        };
+        match direction {
+            Some(ChildType::First) => node_1_indices.push(i),
+            Some(ChildType::Second) => node_2_indices.push(i),
+            None => missing_indices.push(i),
         }
     }
-    // todo find a better way to get majority class
-    let mut m = node_1_targets.clone();
-    let loss = loss_function(node_1_targets, node_2_targets.clone());
 
-    m.extend(&node_2_targets);
-    let mut count_map = HashMap::new();
-    for el in m{
-        *count_map.entry(el).or_insert(0) += 1;
+    let mut node_1_counts = data.class_counts(node_1_indices.iter().copied());
+    let mut node_2_counts = data.class_counts(node_2_indices.iter().copied());
+    let missing_counts = data.class_counts(missing_indices.iter().copied());
+    let merge = |counts: &BTreeMap<isize, f64>, other: &BTreeMap<isize, f64>| {
+        let mut merged = counts.clone();
+        for (class, count) in other {
+            *merged.entry(*class).or_insert(0.0) += count;
+        }
+        merged
+    };
+
+    // send records missing the split column to whichever child gives the lower loss, without
+    // any to learn from send them to the larger child
+    let default_direction = if missing_indices.is_empty() {
+        if node_1_counts.values().sum::<f64>() > node_2_counts.values().sum::<f64>() {
+            ChildType::First
+        } else {
+            ChildType::Second
+        }
+    } else {
+        let loss_first =
+            data.split_loss(loss_fn, &merge(&node_1_counts, &missing_counts), &node_2_counts);
+        let loss_second =
+            data.split_loss(loss_fn, &node_1_counts, &merge(&node_2_counts, &missing_counts));
+        if loss_first < loss_second {
+            ChildType::First
+        } else {
+            ChildType::Second
+        }
+    };
+    // children keep their records in ascending order, see `TrainingData::node_values`
+    match default_direction {
+        ChildType::First => {
+            node_1_indices.extend(missing_indices);
+            node_1_indices.sort_unstable();
+            node_1_counts = merge(&node_1_counts, &missing_counts);
+        }
+        ChildType::Second => {
+            node_2_indices.extend(missing_indices);
+            node_2_indices.sort_unstable();
+            node_2_counts = merge(&node_2_counts, &missing_counts);
+        }
     }
 
-    let majority_class = count_map.into_iter()
-        .max_by(|a, b| a.1.cmp(&b.1))
-        .map(|(k, _v)| k);
+    let class_counts = merge(&node_1_counts, &node_2_counts);
+    let prediction = data.prediction(&class_counts);
+    let impurity = data.impurity(loss_fn, &class_counts);
+    let loss = data.split_loss(loss_fn, &node_1_counts, &node_2_counts);
 
     SplitResult {
         value: split.value,
+        categories: split.categories.clone(),
+        second_categories: split.second_categories.clone(),
         column: split.column,
         loss,
+        impurity,
         node_1_indices,
         node_2_indices,
-        majority_class: *majority_class.unwrap()
+        node_1_weight: node_1_counts.values().sum(),
+        node_2_weight: node_2_counts.values().sum(),
+        default_direction,
+        encoded_prediction: prediction.unwrap(),
+        encoded_class_counts: class_counts,
     }
 }
 
-// todo docs
-#[derive(Debug)]
-pub struct TreeSplit {
-    value: f64,
-    column: usize,
-    loss: Option<f64>,
+/// A split that keeps every record of a node together, for nodes that can't be split
+fn leaf_split(
+    data: &TrainingData<'_>,
+    loss_fn: &LossFunction,
+    filter: Option<&[usize]>,
+) -> Option<SplitResult> {
+    let node_indices = data.records(filter).collect::<Vec<_>>();
+    let class_counts = data.class_counts(node_indices.iter().copied());
+    let impurity = data.impurity(loss_fn, &class_counts);
+
+    Some(SplitResult {
+        value: f64::NAN,
+        categories: None,
+        second_categories: None,
+        column: 0,
+        loss: impurity,
+        impurity,
+        node_1_weight: class_counts.values().sum(),
+        node_2_weight: 0.0,
+        node_1_indices: node_indices,
+        node_2_indices: vec![],
+        default_direction: ChildType::First,
+        encoded_prediction: data.prediction(&class_counts)?,
+        encoded_class_counts: class_counts,
+    })
 }
+
+/// The most common class in a set of class counts, ties are broken in favour of the largest class
+pub(crate) fn majority_class(class_counts: &BTreeMap<isize, f64>) -> Option<isize> {
+    class_counts
+        .iter()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(class, _)| *class)
+}
+
 // todo docs
-struct BestSplit {
+#[derive(Debug)]
+pub(crate) struct TreeSplit {
     value: f64,
+    categories: Option<CategorySet>,
+    second_categories: Option<CategorySet>,
     column: usize,
-    loss: Option<f64>,
-    node_1_indices: Vec<usize>,
-    node_2_indices: Vec<usize>,
 }
 
 /// Generate all possible branch splits for a given set of features
-/// todo docs
-/// todo this is O(col) + 2 * O(col * row), expensive!! Try and reduce this
-pub async fn generate_splits(features: &[Vec<f64>]) -> Vec<TreeSplit> {
-    let no_columns = features[0].len();
+///
+/// Every distinct value of each non-categorical column is a candidate threshold. Sparse features
+/// have no splits generated up front, see [`best_sparse_split`].
+pub(crate) async fn generate_splits(
+    features: &TrainingFeatures<'_>,
+    categorical_features: &[usize],
+) -> Vec<TreeSplit> {
+    let mut all_splits = vec![];
+    let TrainingFeatures::Dense(features) = features else {
+        return all_splits;
+    };
+
+    for (col, column) in features.columns().enumerate() {
+        // categorical columns are split on subsets of categories, which depend on the node
+        if categorical_features.contains(&col) {
+            continue;
+        }
+        // missing values can't be split on, they're routed by each node's default direction
+        let mut values = column
+            .iter()
+            .copied()
+            .filter(|value| !value.is_nan())
+            .collect::<Vec<_>>();
+        values.sort_by(|a, b| a.total_cmp(b));
+        values.dedup();
+        for val in values {
+            all_splits.push(TreeSplit {
+                value: val,
+                categories: None,
+                second_categories: None,
+                column: col,
+            })
+        }
+    }
+
+    all_splits
+}
 
-    let mut columns = vec![];
+/// The best threshold split of the non-categorical columns of sparse features for the records in
+/// a node, or `None` if no column can split them
+///
+/// Only the stored values of each column are visited, the records without one all have a value of
+/// zero so their class counts are the node's less those of the stored values. Splits are
+/// evaluated in the same order as [`generate_splits`] so ties are broken the same way.
+fn best_sparse_split(
+    csc: &CscMatrix,
+    data: &TrainingData<'_>,
+    loss_fn: &LossFunction,
+    filter: Option<&[usize]>,
+    categorical_features: &[usize],
+    min_leaf_weight: f64,
+) -> Option<TreeSplit> {
+    let node_counts = data.class_counts(data.records(filter));
+    let n_node_records = filter.map_or(data.targets.len(), |filter| filter.len());
+    let add = |counts: &mut BTreeMap<isize, f64>, other: &BTreeMap<isize, f64>, sign: f64| {
+        for (class, count) in other {
+            *counts.entry(*class).or_insert(0.0) += sign * count;
+        }
+    };
 
-    for _ in 0..no_columns {
-        columns.push(vec![]);
+    let mut best_split = None;
+    let mut min_loss = 1.0;
+    for column in 0..csc.shape().1 {
+        if categorical_features.contains(&column) {
+            continue;
+        }
+        let mut entries = vec![];
+        let mut missing = vec![];
+        for (row, value) in data.stored_node_values(column, filter) {
+            if value == 0.0 {
+                continue;
+            }
+            if value.is_nan() {
+                missing.push(row)
+            } else {
+                entries.push((value, row))
+            }
+        }
+        entries.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let missing_counts = data.class_counts(missing.iter().copied());
+
+        // group the records by value, with every record without a stored value in the zero group
+        let mut groups: Vec<(f64, BTreeMap<isize, f64>)> = vec![];
+        for (value, row) in &entries {
+            match groups.last_mut() {
+                Some((group_value, _)) if group_value == value => (),
+                _ => groups.push((*value, BTreeMap::new())),
+            }
+            let (_, counts) = groups.last_mut().expect("A group was just added");
+            *counts.entry(data.targets[*row]).or_insert(0.0) += data.sample_weights[*row];
+        }
+        if n_node_records > entries.len() + missing.len() {
+            let mut zero_counts = node_counts.clone();
+            add(&mut zero_counts, &missing_counts, -1.0);
+            for (_, counts) in &groups {
+                add(&mut zero_counts, counts, -1.0);
+            }
+            let position = groups.partition_point(|(value, _)| *value < 0.0);
+            groups.insert(position, (0.0, zero_counts));
+        }
+        if groups.is_empty() {
+            continue;
+        }
+
+        // records greater than the threshold go to the first child, the largest value is a
+        // threshold too so records missing a value can be split from every record with one
+        let mut first_counts = node_counts.clone();
+        add(&mut first_counts, &missing_counts, -1.0);
+        let mut second_counts = BTreeMap::new();
+        for (value, counts) in &groups {
+            add(&mut first_counts, counts, -1.0);
+            add(&mut second_counts, counts, 1.0);
+
+            let (loss, first_weight, second_weight) = if missing.is_empty() {
+                (
+                    data.split_loss(loss_fn, &first_counts, &second_counts),
+                    first_counts.values().sum::<f64>(),
+                    second_counts.values().sum::<f64>(),
+                )
+            } else {
+                let mut first_with_missing = first_counts.clone();
+                add(&mut first_with_missing, &missing_counts, 1.0);
+                let mut second_with_missing = second_counts.clone();
+                add(&mut second_with_missing, &missing_counts, 1.0);
+                let loss_first = data.split_loss(loss_fn, &first_with_missing, &second_counts);
+                let loss_second = data.split_loss(loss_fn, &first_counts, &second_with_missing);
+                if loss_first < loss_second {
+                    (
+                        loss_first,
+                        first_with_missing.values().sum::<f64>(),
+                        second_counts.values().sum::<f64>(),
+                    )
+                } else {
+                    (
+                        loss_second,
+                        first_counts.values().sum::<f64>(),
+                        second_with_missing.values().sum::<f64>(),
+                    )
+                }
+            };
+            if loss < min_loss && first_weight >= min_leaf_weight && second_weight >= min_leaf_weight
+            {
+                min_loss = loss;
+                best_split = Some(TreeSplit {
+                    value: *value,
+                    categories: None,
+                    second_categories: None,
+                    column,
+                });
+            }
+        }
     }
+    best_split
+}
 
-    for row in features {
-        for i in 0..no_columns - 1 {
-            columns[i].push(row[i])
+/// Generate candidate category subset splits of a categorical column for the records in a node
+///
+/// Categories are put in order and every split of that ordering into a lower and upper group is
+/// a candidate. With two classes categories are ordered by the proportion of their records in the
+/// second class, which is guaranteed to contain the best subset. With more classes they're grouped
+/// with Fisher's method, ordered along the first principal component of their class
+/// distributions (Coppersmith, Hong & Hosking, 1999) so categories with similar distributions end
+/// up in the same group.
+fn generate_categorical_splits(
+    data: &TrainingData<'_>,
+    column: usize,
+    filter: Option<&[usize]>,
+) -> Vec<TreeSplit> {
+    let mut category_counts: BTreeMap<usize, BTreeMap<isize, f64>> = BTreeMap::new();
+    let mut stored = vec![];
+    for (i, value) in data.stored_node_values(column, filter) {
+        stored.push(i);
+        if let Some(category) = as_category(value) {
+            *category_counts
+                .entry(category)
+                .or_default()
+                .entry(data.targets[i])
+                .or_insert(0.0) += data.sample_weights[i];
+        }
+    }
+    // records without a stored sparse value are all category zero, their class counts are the
+    // node's less those of the stored values
+    let n_node_records = filter.map_or(data.targets.len(), |filter| filter.len());
+    if n_node_records > stored.len() {
+        let mut zero_records = BTreeMap::new();
+        for i in data.records(filter) {
+            *zero_records.entry(data.targets[i]).or_insert(0usize) += 1;
+        }
+        for i in &stored {
+            *zero_records.entry(data.targets[*i]).or_insert(0) -= 1;
+        }
+        let mut zero_counts = data.class_counts(data.records(filter));
+        for (class, count) in data.class_counts(stored) {
+            *zero_counts.entry(class).or_insert(0.0) -= count;
+        }
+        zero_counts.retain(|class, _| zero_records[class] > 0);
+        let counts = category_counts.entry(0).or_default();
+        for (class, count) in zero_counts {
+            *counts.entry(class).or_insert(0.0) += count;
         }
     }
+    let classes = category_counts
+        .values()
+        .flat_map(|class_counts| class_counts.keys().copied())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    if category_counts.len() < 2 || classes.len() < 2 {
+        return vec![];
+    }
 
-    let mut all_splits = vec![];
+    // the proportion of each category's records in each class
+    let distributions = category_counts
+        .iter()
+        .map(|(category, class_counts)| {
+            let total = class_counts.values().sum::<f64>();
+            let proportions = classes
+                .iter()
+                .map(|class| match total {
+                    0.0 => 0.0,
+                    _ => class_counts.get(class).copied().unwrap_or_default() / total,
+                })
+                .collect::<Vec<_>>();
+            (*category, total, proportions)
+        })
+        .collect::<Vec<_>>();
+    let direction = match classes.len() {
+        2 => vec![0.0, 1.0],
+        _ => first_principal_component(&distributions),
+    };
+    let position = |proportions: &[f64]| -> f64 {
+        proportions.iter().zip(&direction).map(|(p, d)| p * d).sum()
+    };
+    let mut ordered_categories = distributions
+        .iter()
+        .map(|(category, _, proportions)| (*category, position(proportions)))
+        .collect::<Vec<_>>();
+    ordered_categories.sort_by(|a, b| a.1.total_cmp(&b.1));
 
-    for col in 0..no_columns - 1 {
-        columns[col].sort_by(|a, b| a.partial_cmp(b).expect("NaN in vector"));
-        columns[col].dedup();
-        for val in &columns[col] {
-            all_splits.push(TreeSplit {
-                value: *val,
-                column: col,
-                loss: None,
-            })
+    (1..ordered_categories.len())
+        .map(|n_lower| {
+            let group = |categories: &[(usize, f64)]| {
+                categories.iter().map(|(category, _)| *category).collect()
+            };
+            TreeSplit {
+                value: f64::NAN,
+                categories: Some(group(&ordered_categories[..n_lower])),
+                second_categories: Some(group(&ordered_categories[n_lower..])),
+                column,
+            }
+        })
+        .collect()
+}
+
+/// The direction of greatest variance of the class distributions of a set of categories,
+/// weighting each category by the total weight of its records
+///
+/// # Arguments
+/// * `distributions` - the category, total weight and proportion of records in each class of
+///   each category
+fn first_principal_component(distributions: &[(usize, f64, Vec<f64>)]) -> Vec<f64> {
+    let n_classes = distributions[0].2.len();
+    let total_weight = distributions.iter().map(|(_, weight, _)| weight).sum::<f64>();
+    let mut mean = vec![0.0; n_classes];
+    if total_weight > 0.0 {
+        for (_, weight, proportions) in distributions {
+            for (mean, p) in mean.iter_mut().zip(proportions) {
+                *mean += weight * p / total_weight;
+            }
+        }
+    }
+    let mut covariance = vec![vec![0.0; n_classes]; n_classes];
+    for (_, weight, proportions) in distributions {
+        for i in 0..n_classes {
+            for j in 0..n_classes {
+                covariance[i][j] +=
+                    weight * (proportions[i] - mean[i]) * (proportions[j] - mean[j]);
+            }
         }
     }
 
-    all_splits
+    // power iteration, starting from the class that varies most
+    let start = (0..n_classes)
+        .max_by(|a, b| covariance[*a][*a].total_cmp(&covariance[*b][*b]))
+        .unwrap_or_default();
+    let mut component = covariance[start].clone();
+    for _ in 0..100 {
+        let norm = component.iter().map(|c| c * c).sum::<f64>().sqrt();
+        if norm == 0.0 {
+            break;
+        }
+        component.iter_mut().for_each(|c| *c /= norm);
+        component = covariance
+            .iter()
+            .map(|row| row.iter().zip(&component).map(|(a, c)| a * c).sum())
+            .collect();
+    }
+    component
 }
 
 pub(crate) async fn create_node_data(split: &SplitResult) -> NodeData {
-    let node_type = match split.loss {
-        y if y == 0f64 => NodeType::Leaf,
-        _ => NodeType::Branch,
+    // a node is a leaf once it's pure or the split can't separate its records, a split with zero
+    // loss separates the classes perfectly so its children still need to be built
+    let node_type = if split.impurity == 0f64
+        || split.node_1_indices.is_empty()
+        || split.node_2_indices.is_empty()
+    {
+        NodeType::Leaf
+    } else {
+        NodeType::Branch
     };
 
     NodeData {
         node_type,
         column: split.column,
         value: split.value,
+        categories: split.categories.clone(),
+        second_categories: split.second_categories.clone(),
         loss: split.loss,
-        majority_class: split.majority_class
+        impurity: split.impurity,
+        n_samples: split.node_1_indices.len() + split.node_2_indices.len(),
+        weighted_n_samples: split.node_1_weight + split.node_2_weight,
+        default_direction: split.default_direction,
+        encoded_prediction: split.encoded_prediction,
+        encoded_class_counts: split.encoded_class_counts.clone(),
     }
 }
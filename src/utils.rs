@@ -1,49 +1,41 @@
-use std::fs::read_to_string;
+use crate::io::{read_csv, CsvOptionsBuilder};
 
-fn load_dataset() -> (Vec<Vec<f64>>, Vec<isize>) {
-    let mut features = vec![];
-    let mut targets = vec![];
-    for _ in 0..1372 {
-        features.push(vec![]);
-    }
-    let file = read_to_string("./data/data_banknote_authentication.txt")
-        .expect("Coudln't read file to string");
-    let rows = file.split("\r\n").collect::<Vec<&str>>();
-    for (i, row) in rows.into_iter().enumerate() {
-        let vals = row.split(",").collect::<Vec<&str>>();
-        for (c, val) in vals.into_iter().enumerate() {
-            if c < 4 {
-                features[i].push(val.parse::<f64>().unwrap());
-            } else {
-                targets.push(val.parse::<isize>().unwrap())
-            }
-        }
-    }
-    (features, targets)
+pub fn load_milk_train_dataset() -> (Vec<Vec<f64>>, Vec<isize>) {
+    load_milk_dataset("train")
 }
 
-pub fn load_milk_train_dataset() -> (Vec<Vec<f64>>, Vec<isize>) {
-    let features_file =
-        read_to_string("./data/train_features.csv").expect("Coudln't read file to string");
-    let rows = features_file.split("\n").collect::<Vec<&str>>();
-    let n_rows = rows.len();
-    let mut features = vec![];
-    for _ in 0..n_rows {
-        features.push(vec![]);
-    }
-    for (i, row) in rows.into_iter().enumerate() {
-        let vals = row.split(",").collect::<Vec<&str>>();
-        for val in vals {
-            features[i].push(val.parse::<f64>().unwrap())
-        }
-    }
+pub fn load_milk_test_dataset() -> (Vec<Vec<f64>>, Vec<isize>) {
+    load_milk_dataset("test")
+}
+
+fn load_milk_dataset(split: &str) -> (Vec<Vec<f64>>, Vec<isize>) {
+    let data_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/milk/data");
+    let features_options = CsvOptionsBuilder::default()
+        .has_headers(false)
+        .build()
+        .unwrap();
+    let features = read_csv(
+        format!("{data_dir}/{split}_features.csv"),
+        &features_options,
+    )
+    .expect("Couldn't read features")
+    .features;
+
+    let targets_options = CsvOptionsBuilder::default()
+        .has_headers(false)
+        .target_column(0usize)
+        .build()
+        .unwrap();
+    let targets = read_csv(format!("{data_dir}/{split}_target.csv"), &targets_options)
+        .expect("Couldn't read targets")
+        .targets
+        .unwrap()
+        .iter()
+        .map(|target| target.parse::<isize>().unwrap())
+        .collect();
 
-    let targets_file =
-        read_to_string("./data/train_target.csv").expect("Coudln't read file to string");
-    let rows = targets_file.split("\n").collect::<Vec<&str>>();
-    let mut targets = vec![];
-    for val in rows {
-        targets.push(val.parse::<isize>().unwrap())
-    }
+    let features = (0..features.n_rows())
+        .map(|row| features.row(row))
+        .collect();
     (features, targets)
 }